@@ -0,0 +1,79 @@
+#[cfg(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+use minifb::Event;
+use minifb::{Key, Window, WindowOptions};
+
+const WIDTH: usize = 640;
+const HEIGHT: usize = 360;
+
+const IDLE_COLOR: u32 = 0x0020_2020;
+const HOVER_COLOR: u32 = 0x0020_4060;
+const MARKER_COLOR: u32 = 0x00ff_ffff;
+
+fn main() {
+    let mut buffer = vec![0u32; WIDTH * HEIGHT];
+
+    let mut window = Window::new(
+        "File drop example - drop files on the window, press ESC to exit",
+        WIDTH,
+        HEIGHT,
+        WindowOptions::default(),
+    )
+    .expect("Unable to create the window");
+
+    window.set_target_fps(60);
+
+    // position of the files being dragged over the window
+    #[allow(unused_mut)]
+    let mut hover: Option<(f32, f32)> = None;
+
+    while window.is_open() && !window.is_key_down(Key::Escape) {
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "freebsd",
+            target_os = "dragonfly",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        for timed_event in window.drain_events() {
+            match timed_event.event {
+                Event::DragEnter { x, y } | Event::DragMove { x, y } => hover = Some((x, y)),
+                Event::DragLeave => hover = None,
+                Event::Drop(paths) => {
+                    hover = None;
+                    for path in paths {
+                        println!("Dropped: {}", path.display());
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        let color = if hover.is_some() {
+            HOVER_COLOR
+        } else {
+            IDLE_COLOR
+        };
+        buffer.iter_mut().for_each(|pixel| *pixel = color);
+
+        // draw a cross where the files would be dropped
+        if let Some((x, y)) = hover {
+            let (x, y) = (x as usize, y as usize);
+            if x < WIDTH && y < HEIGHT {
+                buffer[y * WIDTH..(y + 1) * WIDTH].fill(MARKER_COLOR);
+                for row in buffer.chunks_exact_mut(WIDTH) {
+                    row[x] = MARKER_COLOR;
+                }
+            }
+        }
+
+        window
+            .update_with_buffer(&buffer, WIDTH, HEIGHT)
+            .expect("Unable to update the window");
+    }
+}
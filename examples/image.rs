@@ -12,7 +12,7 @@ fn main() {
     let mut buffer = vec![0u32; reader.output_buffer_size() / 4];
 
     // View of pixels as individual subpixels (avoids allocating a second pixel buffer).
-    let mut u8_buffer = unsafe {
+    let u8_buffer = unsafe {
         std::slice::from_raw_parts_mut(
             buffer.as_mut_ptr() as *mut u8,
             buffer.len() * std::mem::size_of::<u32>(),
@@ -20,7 +20,7 @@ fn main() {
     };
 
     // Read the next frame. Currently this function should only be called once.
-    reader.next_frame(&mut u8_buffer).unwrap();
+    reader.next_frame(u8_buffer).unwrap();
 
     // convert RGBA buffer read by the reader to an ARGB buffer as expected by minifb.
     for (rgba, argb) in u8_buffer.chunks_mut(4).zip(buffer.iter_mut()) {
@@ -40,7 +40,7 @@ fn main() {
 
             // So this code is really slow, but good enough as an example :)
             let mut x = 0.0;
-            for xi in 0..WIDTH {
+            for pixel in row.iter_mut() {
                 let k = 0.1 + (y + (0.148 - time).sin()).cos() + 2.4 * time;
                 let w = 0.9 + (x + (0.628 + time).cos()).cos() - 0.7 * time;
                 let d = (x * x + y * y).sqrt();
@@ -50,7 +50,7 @@ fn main() {
                 let r = ((s + 0.2).cos() * 255.0) as u32;
                 let g = ((s + 0.5).cos() * 255.0) as u32;
                 let b = ((s + 0.7).cos() * 255.0) as u32;
-                row[xi] = (r << 16) | (g << 8) | b;
+                *pixel = (r << 16) | (g << 8) | b;
 
                 x += x_step;
             }
@@ -113,7 +113,7 @@ impl Text {
     pub fn draw(&self, screen: &mut [u32], (mut x, y): (usize, usize), text: &str) {
         for c in text.chars() {
             let mut index = c as usize - ' ' as usize;
-            if index > MICROKNIGHT_LAYOUT.len() as usize {
+            if index > MICROKNIGHT_LAYOUT.len() {
                 index = 0;
             }
 
@@ -0,0 +1,14 @@
+/// An image read from or written to the clipboard
+///
+/// The pixels are stored the same way as the buffers passed to `update_with_buffer` (one `u32`
+/// per pixel, row by row) with the alpha in the upper 8 bits, so each pixel is `0xAARRGGBB`.
+/// Note that the alpha is used as is, so pixels with an alpha of 0 are fully transparent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClipboardImage {
+    /// Width of the image in pixels
+    pub width: usize,
+    /// Height of the image in pixels
+    pub height: usize,
+    /// `width * height` pixels in the `0xAARRGGBB` format
+    pub data: Vec<u32>,
+}
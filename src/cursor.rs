@@ -0,0 +1,59 @@
+use crate::{check_buffer_size, Error, Result};
+
+/// A cursor image, set with `Window::set_custom_cursor`
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    not(any(
+        target_os = "linux",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )),
+    allow(dead_code)
+)]
+pub struct CustomCursor {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) hotspot: (usize, usize),
+    pub(crate) data: Vec<u32>,
+}
+
+impl CustomCursor {
+    /// Creates a cursor from `width * height` pixels in the `0xAARRGGBB` format (row by row,
+    /// same as the buffers passed to `update_with_buffer`). The alpha is not premultiplied and
+    /// pixels with an alpha of 0 are fully transparent. `hotspot` is the pixel that is at the
+    /// mouse position, (0, 0) being the upper left corner.
+    ///
+    /// Returns an error if `data` is too small or the hotspot is outside of the image.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// // a 16 x 16 white square with the hotspot in the center
+    /// let cursor = CustomCursor::from_argb(16, 16, (8, 8), &[0xffff_ffff; 16 * 16]).unwrap();
+    /// ```
+    pub fn from_argb(
+        width: usize,
+        height: usize,
+        hotspot: (usize, usize),
+        data: &[u32],
+    ) -> Result<CustomCursor> {
+        check_buffer_size(data, width, height, width)?;
+
+        if hotspot.0 >= width || hotspot.1 >= height {
+            return Err(Error::UpdateFailed(format!(
+                "Cursor hotspot {:?} is outside of the {} x {} image",
+                hotspot, width, height
+            )));
+        }
+
+        Ok(CustomCursor {
+            width,
+            height,
+            hotspot,
+            data: data[..width * height].to_vec(),
+        })
+    }
+}
@@ -10,6 +10,8 @@ pub enum Error {
     WindowCreate(String),
     /// Unable to Update
     UpdateFailed(String),
+    /// The operation isn't supported by the platform or windowing system
+    NotSupported(String),
 }
 
 impl fmt::Display for Error {
@@ -19,6 +21,7 @@ impl fmt::Display for Error {
             Error::MenuExists(_) => write!(formatter, "Menu already exists"),
             Error::WindowCreate(_) => write!(formatter, "Failed to create window"),
             Error::UpdateFailed(_) => write!(formatter, "Failed to Update"),
+            Error::NotSupported(_) => write!(formatter, "Operation not supported"),
         }
     }
 }
@@ -30,6 +33,7 @@ impl fmt::Debug for Error {
             Error::MenuExists(ref e) => write!(fmt, "{}, {:?}", self, e),
             Error::WindowCreate(ref e) => write!(fmt, "{}, {:?}", self, e),
             Error::UpdateFailed(ref e) => write!(fmt, "{}, {:?}", self, e),
+            Error::NotSupported(ref e) => write!(fmt, "{}, {:?}", self, e),
         }
     }
 }
@@ -0,0 +1,295 @@
+use std::{path::PathBuf, time::Duration};
+
+#[cfg(feature = "gamepad")]
+use crate::{GamepadAxis, GamepadButton};
+use crate::{Key, Modifiers, MouseButton, Scancode};
+
+/// Events that are generated by a window. All events that happened since the previous call to
+/// `update` (or `update_with_buffer`) can be retrieved with `Window::poll_events` or
+/// `Window::drain_events` in the order they were received.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Event {
+    /// A key was pressed. `key` depends on the keyboard layout while `scancode` is the physical
+    /// key. Keys that has no `Key` mapping are reported as `Key::Unknown`. `modifiers` are the
+    /// modifiers active after the key was pressed. `repeat` is set for the events repeated
+    /// while the key is held down, see `Window::set_key_repeat_delay`
+    KeyDown {
+        key: Key,
+        scancode: Scancode,
+        modifiers: Modifiers,
+        repeat: bool,
+    },
+    /// A key was released. See `KeyDown` for the fields
+    KeyUp {
+        key: Key,
+        scancode: Scancode,
+        modifiers: Modifiers,
+    },
+    /// Text input. Unlike `KeyDown` this is the translated character and takes the keyboard
+    /// layout and dead/compose keys into account
+    Char(char),
+    /// The text being composed by an input method (IME) changed. `cursor` is the byte range of
+    /// the cursor (or selection) within `text`, `None` means that no cursor should be shown. An
+    /// empty `text` means that the composition ended or was cancelled
+    Preedit {
+        text: String,
+        cursor: Option<(usize, usize)>,
+    },
+    /// Text was committed, either typed directly or as the result of a composition by an input
+    /// method. `Char` events are sent for the characters as well
+    Commit(String),
+    /// The mouse moved. The position is in buffer coordinates (same as `MouseMode::Pass`). Every
+    /// position reported by the windowing system is sent (not just the one at `update`) which
+    /// allows smooth strokes in drawing programs
+    MouseMove { x: f32, y: f32 },
+    /// A mouse button was pressed or released. `clicks` is 1 for a single click, 2 for a double
+    /// click and so on (see `Window::get_click_count`), releases have the count of the press
+    MouseButton {
+        button: MouseButton,
+        pressed: bool,
+        clicks: u32,
+    },
+    /// The scroll wheel (or trackpad) was used. Positive values scrolls up and to the right.
+    /// `pixels` is the distance to scroll smooth content by and `notches` is in clicks of a
+    /// mouse wheel, for example to scroll by lines. `notches` are computed from the pixels for
+    /// sources that doesn't have notches
+    Scroll {
+        source: ScrollSource,
+        pixels: (f32, f32),
+        notches: (f32, f32),
+    },
+    /// The window was resized
+    Resize(WindowSize),
+    /// The window gained (true) or lost (false) keyboard focus
+    Focus(bool),
+    /// The window was maximized (true) or restored from being maximized (false)
+    Maximized(bool),
+    /// The window was minimized (true) or restored from being minimized (false). Not reported
+    /// on Wayland
+    Minimized(bool),
+    /// Files are being dragged over the window. The position is in buffer coordinates (same as
+    /// `MouseMode::Pass`)
+    DragEnter { x: f32, y: f32 },
+    /// Files being dragged over the window moved
+    DragMove { x: f32, y: f32 },
+    /// The files being dragged left the window or the drag was cancelled
+    DragLeave,
+    /// Files were dropped on the window
+    Drop(Vec<PathBuf>),
+    /// A finger touched, moved on or was lifted from a touchscreen. `id` identifies the finger
+    /// from `TouchPhase::Begin` until `TouchPhase::End` (or `TouchPhase::Cancel`) and may be
+    /// reused afterwards. The position is in buffer coordinates (same as `MouseMode::Pass`)
+    Touch {
+        id: u64,
+        phase: TouchPhase,
+        x: f32,
+        y: f32,
+    },
+    /// A tablet pen moved over the window, touched or was lifted from the tablet, or one of its
+    /// values (pressure, tilt, buttons) changed. See `Window::get_pen_state`
+    Pen(PenState),
+    /// The tablet pen left the window or was moved away from the tablet
+    PenLeave,
+    /// A gamepad was connected. The value is the id of the gamepad, see `Window::get_gamepads`
+    #[cfg(feature = "gamepad")]
+    GamepadConnected(u32),
+    /// A gamepad was disconnected
+    #[cfg(feature = "gamepad")]
+    GamepadDisconnected(u32),
+    /// A gamepad button was pressed or released
+    #[cfg(feature = "gamepad")]
+    GamepadButton {
+        id: u32,
+        button: GamepadButton,
+        pressed: bool,
+    },
+    /// A gamepad axis moved. See `GamepadAxis` for the range of `value`
+    #[cfg(feature = "gamepad")]
+    GamepadAxis {
+        id: u32,
+        axis: GamepadAxis,
+        value: f32,
+    },
+    /// The user asked for the window to be closed (for example by pressing the close button)
+    CloseRequested,
+}
+
+/// What generated a `Event::Scroll`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScrollSource {
+    /// A mouse wheel. The notches are whole numbers unless the wheel has a high resolution
+    Wheel,
+    /// Fingers on a trackpad
+    Finger,
+    /// Continuous scrolling without fingers or a wheel, like a trackpoint used with the middle
+    /// button held down
+    Continuous,
+}
+
+/// The phase of a `Event::Touch`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TouchPhase {
+    /// A finger touched the screen
+    Begin,
+    /// A finger moved
+    Move,
+    /// A finger was lifted from the screen
+    End,
+    /// The touch was taken over by the system (for example by a gesture) and no more events
+    /// will be sent for it
+    Cancel,
+}
+
+/// A finger currently touching the screen, as returned by `Window::get_touches`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Touch {
+    /// Identifies the finger, same as the `id` of `Event::Touch`
+    pub id: u64,
+    /// Position in buffer coordinates (same as `MouseMode::Pass`)
+    pub x: f32,
+    /// Position in buffer coordinates (same as `MouseMode::Pass`)
+    pub y: f32,
+}
+
+/// The state of a drawing tablet pen, as reported by `Event::Pen` and `Window::get_pen_state`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PenState {
+    /// Position in buffer coordinates (same as `MouseMode::Pass`)
+    pub x: f32,
+    /// Position in buffer coordinates (same as `MouseMode::Pass`)
+    pub y: f32,
+    /// Pressure of the tip, from 0.0 (not touching) to 1.0 (full pressure)
+    pub pressure: f32,
+    /// Tilt towards the right of the tablet in degrees, 0.0 is perpendicular to the tablet
+    pub tilt_x: f32,
+    /// Tilt towards the bottom of the tablet in degrees, 0.0 is perpendicular to the tablet
+    pub tilt_y: f32,
+    /// The eraser end of the pen (or a dedicated eraser tool) is used
+    pub eraser: bool,
+    /// The tip touches the tablet
+    pub in_contact: bool,
+    /// The lower and upper buttons on the side of the pen
+    pub barrel_buttons: [bool; 2],
+}
+
+/// The size of a window, as reported by `Event::Resize` and the resize callback.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WindowSize {
+    /// Width of the window in pixels (same as `Window::get_size`)
+    pub width: usize,
+    /// Height of the window in pixels (same as `Window::get_size`)
+    pub height: usize,
+    /// Width of the window in buffer pixels, that is the width divided by the window `Scale`
+    pub logical_width: usize,
+    /// Height of the window in buffer pixels, that is the height divided by the window `Scale`
+    pub logical_height: usize,
+}
+
+impl WindowSize {
+    pub(crate) fn new(width: usize, height: usize, scale: usize) -> WindowSize {
+        WindowSize {
+            width,
+            height,
+            logical_width: width / scale,
+            logical_height: height / scale,
+        }
+    }
+}
+
+/// An `Event` together with the time it was generated.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimedEvent {
+    /// Time of the event as reported by the windowing system. The starting point of the clock
+    /// is unspecified so this is only useful for comparing events with each other. Events that
+    /// doesn't carry a time from the windowing system gets the time of the previous event.
+    pub time: Duration,
+    /// The event itself
+    pub event: Event,
+}
+
+/// Events collected by a backend between two calls to `update`
+#[cfg_attr(
+    not(any(
+        target_os = "linux",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )),
+    allow(dead_code)
+)]
+pub(crate) struct EventBuffer {
+    events: Vec<TimedEvent>,
+    time: Duration,
+    resize_callback: Option<Box<dyn FnMut(WindowSize)>>,
+}
+
+#[cfg_attr(
+    not(any(
+        target_os = "linux",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )),
+    allow(dead_code)
+)]
+impl EventBuffer {
+    pub fn new() -> EventBuffer {
+        EventBuffer {
+            events: Vec::new(),
+            time: Duration::from_secs(0),
+            resize_callback: None,
+        }
+    }
+
+    #[inline]
+    pub fn set_resize_callback(&mut self, callback: Box<dyn FnMut(WindowSize)>) {
+        self.resize_callback = Some(callback);
+    }
+
+    /// Sets the time (in milliseconds) used for the events pushed after this call
+    #[inline]
+    pub fn set_time_ms(&mut self, time: u32) {
+        self.time = Duration::from_millis(time as u64);
+    }
+
+    /// The time (in milliseconds) of the last event pushed
+    #[inline]
+    pub fn time_ms(&self) -> u32 {
+        self.time.as_millis() as u32
+    }
+
+    #[inline]
+    pub fn push(&mut self, event: Event) {
+        self.events.push(TimedEvent {
+            time: self.time,
+            event,
+        });
+    }
+
+    /// Pushes a `Event::Resize` and calls the resize callback (if set)
+    pub fn push_resize(&mut self, size: WindowSize) {
+        if let Some(callback) = &mut self.resize_callback {
+            callback(size);
+        }
+
+        self.push(Event::Resize(size));
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    #[inline]
+    pub fn iter(&self) -> std::slice::Iter<'_, TimedEvent> {
+        self.events.iter()
+    }
+
+    #[inline]
+    pub fn drain(&mut self) -> std::vec::Drain<'_, TimedEvent> {
+        self.events.drain(..)
+    }
+}
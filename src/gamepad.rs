@@ -0,0 +1,85 @@
+/// Buttons of a gamepad. The face buttons are named by their position (`South` is A on an Xbox
+/// controller and Cross on a PlayStation controller), the other ones after the Xbox layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftShoulder,
+    RightShoulder,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    /// The button in the middle of the controller (Xbox or PS button)
+    Mode,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+/// Axes of a gamepad. The sticks go from -1.0 to 1.0 (with positive values being right and
+/// down) and the triggers from 0.0 (released) to 1.0 (fully pressed).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    LeftTrigger,
+    RightStickX,
+    RightStickY,
+    RightTrigger,
+}
+
+pub(crate) const GAMEPAD_BUTTON_COUNT: usize = GamepadButton::DPadRight as usize + 1;
+pub(crate) const GAMEPAD_AXIS_COUNT: usize = GamepadAxis::RightTrigger as usize + 1;
+
+/// A connected gamepad, as returned by `Window::get_gamepads`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub struct Gamepad {
+    pub(crate) id: u32,
+    pub(crate) name: String,
+    pub(crate) buttons: [bool; GAMEPAD_BUTTON_COUNT],
+    pub(crate) axes: [f32; GAMEPAD_AXIS_COUNT],
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+impl Gamepad {
+    pub(crate) fn new(id: u32, name: String) -> Gamepad {
+        Gamepad {
+            id,
+            name,
+            buttons: [false; GAMEPAD_BUTTON_COUNT],
+            axes: [0.0; GAMEPAD_AXIS_COUNT],
+        }
+    }
+
+    /// Identifies the gamepad in the gamepad events. A gamepad that is reconnected gets a new id.
+    #[inline]
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// The name of the gamepad as reported by the device
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Check if a button is held down
+    #[inline]
+    pub fn is_button_down(&self, button: GamepadButton) -> bool {
+        self.buttons[button as usize]
+    }
+
+    /// Returns the position of an axis, see `GamepadAxis` for the ranges. Axes that the gamepad
+    /// doesn't have are always 0.0
+    #[inline]
+    pub fn get_axis(&self, axis: GamepadAxis) -> f32 {
+        self.axes[axis as usize]
+    }
+}
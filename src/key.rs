@@ -125,3 +125,118 @@ pub enum Key {
 
     Count = 107,
 }
+
+bitflags::bitflags! {
+    /// The keyboard modifiers that are active. Returned by `Window::get_modifiers` and reported
+    /// together with key events.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct Modifiers: u32 {
+        /// Left or right shift is held
+        const SHIFT = 1 << 0;
+        /// Left or right control is held
+        const CTRL = 1 << 1;
+        /// Left or right alt is held
+        const ALT = 1 << 2;
+        /// Left or right super (Windows/Command) key is held
+        const SUPER = 1 << 3;
+        /// Caps Lock is on
+        const CAPS_LOCK = 1 << 4;
+        /// Num Lock is on
+        const NUM_LOCK = 1 << 5;
+    }
+}
+
+/// The physical location of a key on the keyboard, independent of the keyboard layout. This is
+/// useful for things like WASD movement in games which should use the same physical keys on
+/// AZERTY or Dvorak layouts.
+///
+/// The value is the Linux evdev key code (`KEY_*` in `linux/input-event-codes.h`) and the
+/// constants are named after the keys on an US keyboard.
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy)]
+pub struct Scancode(pub u32);
+
+impl Scancode {
+    pub const ESCAPE: Scancode = Scancode(1);
+    pub const KEY1: Scancode = Scancode(2);
+    pub const KEY2: Scancode = Scancode(3);
+    pub const KEY3: Scancode = Scancode(4);
+    pub const KEY4: Scancode = Scancode(5);
+    pub const KEY5: Scancode = Scancode(6);
+    pub const KEY6: Scancode = Scancode(7);
+    pub const KEY7: Scancode = Scancode(8);
+    pub const KEY8: Scancode = Scancode(9);
+    pub const KEY9: Scancode = Scancode(10);
+    pub const KEY0: Scancode = Scancode(11);
+    pub const MINUS: Scancode = Scancode(12);
+    pub const EQUAL: Scancode = Scancode(13);
+    pub const BACKSPACE: Scancode = Scancode(14);
+    pub const TAB: Scancode = Scancode(15);
+    pub const Q: Scancode = Scancode(16);
+    pub const W: Scancode = Scancode(17);
+    pub const E: Scancode = Scancode(18);
+    pub const R: Scancode = Scancode(19);
+    pub const T: Scancode = Scancode(20);
+    pub const Y: Scancode = Scancode(21);
+    pub const U: Scancode = Scancode(22);
+    pub const I: Scancode = Scancode(23);
+    pub const O: Scancode = Scancode(24);
+    pub const P: Scancode = Scancode(25);
+    pub const LEFT_BRACKET: Scancode = Scancode(26);
+    pub const RIGHT_BRACKET: Scancode = Scancode(27);
+    pub const ENTER: Scancode = Scancode(28);
+    pub const LEFT_CTRL: Scancode = Scancode(29);
+    pub const A: Scancode = Scancode(30);
+    pub const S: Scancode = Scancode(31);
+    pub const D: Scancode = Scancode(32);
+    pub const F: Scancode = Scancode(33);
+    pub const G: Scancode = Scancode(34);
+    pub const H: Scancode = Scancode(35);
+    pub const J: Scancode = Scancode(36);
+    pub const K: Scancode = Scancode(37);
+    pub const L: Scancode = Scancode(38);
+    pub const SEMICOLON: Scancode = Scancode(39);
+    pub const APOSTROPHE: Scancode = Scancode(40);
+    pub const BACKQUOTE: Scancode = Scancode(41);
+    pub const LEFT_SHIFT: Scancode = Scancode(42);
+    pub const BACKSLASH: Scancode = Scancode(43);
+    pub const Z: Scancode = Scancode(44);
+    pub const X: Scancode = Scancode(45);
+    pub const C: Scancode = Scancode(46);
+    pub const V: Scancode = Scancode(47);
+    pub const B: Scancode = Scancode(48);
+    pub const N: Scancode = Scancode(49);
+    pub const M: Scancode = Scancode(50);
+    pub const COMMA: Scancode = Scancode(51);
+    pub const PERIOD: Scancode = Scancode(52);
+    pub const SLASH: Scancode = Scancode(53);
+    pub const RIGHT_SHIFT: Scancode = Scancode(54);
+    pub const LEFT_ALT: Scancode = Scancode(56);
+    pub const SPACE: Scancode = Scancode(57);
+    pub const CAPS_LOCK: Scancode = Scancode(58);
+    pub const F1: Scancode = Scancode(59);
+    pub const F2: Scancode = Scancode(60);
+    pub const F3: Scancode = Scancode(61);
+    pub const F4: Scancode = Scancode(62);
+    pub const F5: Scancode = Scancode(63);
+    pub const F6: Scancode = Scancode(64);
+    pub const F7: Scancode = Scancode(65);
+    pub const F8: Scancode = Scancode(66);
+    pub const F9: Scancode = Scancode(67);
+    pub const F10: Scancode = Scancode(68);
+    pub const F11: Scancode = Scancode(87);
+    pub const F12: Scancode = Scancode(88);
+    pub const RIGHT_CTRL: Scancode = Scancode(97);
+    pub const RIGHT_ALT: Scancode = Scancode(100);
+    pub const HOME: Scancode = Scancode(102);
+    pub const UP: Scancode = Scancode(103);
+    pub const PAGE_UP: Scancode = Scancode(104);
+    pub const LEFT: Scancode = Scancode(105);
+    pub const RIGHT: Scancode = Scancode(106);
+    pub const END: Scancode = Scancode(107);
+    pub const DOWN: Scancode = Scancode(108);
+    pub const PAGE_DOWN: Scancode = Scancode(109);
+    pub const INSERT: Scancode = Scancode(110);
+    pub const DELETE: Scancode = Scancode(111);
+    pub const LEFT_SUPER: Scancode = Scancode(125);
+    pub const RIGHT_SUPER: Scancode = Scancode(126);
+}
@@ -1,6 +1,42 @@
 use web_time::{Duration, Instant};
 
-use crate::{InputCallback, Key, KeyRepeat};
+use crate::{InputCallback, Key, KeyRepeat, Modifiers, Scancode};
+
+// Covers all key codes defined by Linux (KEY_MAX is 0x2ff)
+const SCANCODE_COUNT: usize = 768;
+
+/// The key being held down that generates repeat events, see `KeyHandler::next_repeat`
+#[cfg(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+struct RepeatingKey {
+    key: Key,
+    scancode: Scancode,
+    pressed: Instant,
+    // time of the press in milliseconds (same clock as the event times)
+    time: u32,
+    count: u32,
+}
+
+/// Key repeat of the backends that generate the repeat events themselves
+#[cfg(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+#[derive(Default)]
+struct RepeatState {
+    // set when the application chose its own repeat delay or rate over the system ones
+    delay_set: bool,
+    rate_set: bool,
+    key: Option<RepeatingKey>,
+}
 
 pub struct KeyHandler {
     pub key_callback: Option<Box<dyn InputCallback>>,
@@ -9,8 +45,18 @@ pub struct KeyHandler {
     keys: [bool; 512],
     keys_prev: [bool; 512],
     keys_down_duration: [f32; 512],
+    scancodes: [bool; SCANCODE_COUNT],
     key_repeat_delay: f32,
     key_repeat_rate: f32,
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    repeat: RepeatState,
+    modifiers: Modifiers,
 }
 
 impl KeyHandler {
@@ -20,21 +66,76 @@ impl KeyHandler {
             keys: [false; 512],
             keys_prev: [false; 512],
             keys_down_duration: [-1.0; 512],
+            scancodes: [false; SCANCODE_COUNT],
             prev_time: Instant::now(),
             delta_time: Duration::from_secs(0),
             key_repeat_delay: 0.250,
             key_repeat_rate: 0.050,
+            #[cfg(any(
+                target_os = "linux",
+                target_os = "freebsd",
+                target_os = "dragonfly",
+                target_os = "netbsd",
+                target_os = "openbsd"
+            ))]
+            repeat: RepeatState::default(),
+            modifiers: Modifiers::empty(),
         }
     }
 
     #[inline]
     pub fn set_key_state(&mut self, key: Key, state: bool) {
         self.keys[key as usize] = state;
+        self.update_modifiers(key);
         if let Some(cb) = &mut self.key_callback {
-            cb.set_key_state(key, state);
+            cb.set_key_state_with_modifiers(key, state, self.modifiers);
+        }
+    }
+
+    #[allow(dead_code)]
+    #[inline]
+    pub fn set_scancode_state(&mut self, scancode: Scancode, state: bool) {
+        if let Some(is_down) = self.scancodes.get_mut(scancode.0 as usize) {
+            *is_down = state;
         }
     }
 
+    #[allow(dead_code)]
+    #[inline]
+    pub fn is_scancode_down(&self, scancode: Scancode) -> bool {
+        self.scancodes
+            .get(scancode.0 as usize)
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Sets the modifiers as reported by the OS. The held modifiers are still updated by
+    /// `set_key_state` as the OS usually reports the state from before the key event.
+    #[allow(dead_code)]
+    #[inline]
+    pub fn set_modifiers(&mut self, modifiers: Modifiers) {
+        self.modifiers = modifiers;
+    }
+
+    #[allow(dead_code)]
+    #[inline]
+    pub fn get_modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    fn update_modifiers(&mut self, key: Key) {
+        let (modifier, left, right) = match key {
+            Key::LeftShift | Key::RightShift => (Modifiers::SHIFT, Key::LeftShift, Key::RightShift),
+            Key::LeftCtrl | Key::RightCtrl => (Modifiers::CTRL, Key::LeftCtrl, Key::RightCtrl),
+            Key::LeftAlt | Key::RightAlt => (Modifiers::ALT, Key::LeftAlt, Key::RightAlt),
+            Key::LeftSuper | Key::RightSuper => (Modifiers::SUPER, Key::LeftSuper, Key::RightSuper),
+            _ => return,
+        };
+
+        let is_down = self.keys[left as usize] || self.keys[right as usize];
+        self.modifiers.set(modifier, is_down);
+    }
+
     pub fn get_keys(&self) -> Vec<Key> {
         let mut keys: Vec<Key> = Vec::new();
 
@@ -109,11 +210,31 @@ impl KeyHandler {
     #[inline]
     pub fn set_key_repeat_delay(&mut self, delay: f32) {
         self.key_repeat_delay = delay;
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "freebsd",
+            target_os = "dragonfly",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        {
+            self.repeat.delay_set = true;
+        }
     }
 
     #[inline]
     pub fn set_key_repeat_rate(&mut self, rate: f32) {
         self.key_repeat_rate = rate;
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "freebsd",
+            target_os = "dragonfly",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        {
+            self.repeat.rate_set = true;
+        }
     }
 
     fn is_key_index_pressed(&self, index: usize, repeat: KeyRepeat) -> bool {
@@ -152,3 +273,77 @@ impl KeyHandler {
         self.keys_prev[idx] && !self.keys[idx]
     }
 }
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+impl KeyHandler {
+    /// Sets the repeat delay and rate (in seconds) from the system settings. These are used
+    /// unless the application has set its own. A delay of `f32::INFINITY` disables repeat.
+    pub fn set_system_key_repeat(&mut self, delay: f32, rate: f32) {
+        if !self.repeat.delay_set {
+            self.key_repeat_delay = delay;
+        }
+        if !self.repeat.rate_set {
+            self.key_repeat_rate = rate;
+        }
+    }
+
+    /// Returns true if the application has set its own repeat delay or rate
+    #[cfg(feature = "x11")]
+    #[inline]
+    pub fn is_key_repeat_overridden(&self) -> bool {
+        self.repeat.delay_set || self.repeat.rate_set
+    }
+
+    /// Starts repeating a key that was pressed at `time` (in milliseconds). Only the last key
+    /// pressed is repeated, same as the OS does.
+    pub fn start_repeat(&mut self, key: Key, scancode: Scancode, time: u32) {
+        self.repeat.key = Some(RepeatingKey {
+            key,
+            scancode,
+            pressed: Instant::now(),
+            time,
+            count: 0,
+        });
+    }
+
+    /// Stops repeating a key that was released
+    pub fn stop_repeat(&mut self, scancode: Scancode) {
+        if let Some(repeating) = &self.repeat.key {
+            if repeating.scancode == scancode {
+                self.repeat.key = None;
+            }
+        }
+    }
+
+    /// Stops repeating, used when the window loses the keyboard focus
+    #[inline]
+    pub fn cancel_repeat(&mut self) {
+        self.repeat.key = None;
+    }
+
+    /// Returns the key, scancode and time (in milliseconds) of the next repeat that is due, to
+    /// be called until it returns `None`
+    pub fn next_repeat(&mut self) -> Option<(Key, Scancode, u32)> {
+        let (delay, rate) = (self.key_repeat_delay, self.key_repeat_rate);
+        let repeating = self.repeat.key.as_mut()?;
+
+        if !delay.is_finite() || rate.is_nan() || rate <= 0.0 {
+            return None;
+        }
+
+        let due = delay + rate * repeating.count as f32;
+        if repeating.pressed.elapsed().as_secs_f32() < due {
+            return None;
+        }
+
+        repeating.count += 1;
+        let time = repeating.time.wrapping_add((due * 1000.0) as u32);
+        Some((repeating.key, repeating.scancode, time))
+    }
+}
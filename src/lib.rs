@@ -15,10 +15,17 @@
 #[macro_use]
 extern crate dlib;
 
+#[cfg(feature = "clipboard-image")]
+mod clipboard;
+mod cursor;
 mod error;
+mod event;
+#[cfg(feature = "gamepad")]
+mod gamepad;
 mod icon;
 mod key;
 mod key_handler;
+mod monitor;
 mod os;
 mod rate;
 
@@ -45,9 +52,16 @@ use os::wasm as imp;
 #[cfg(target_os = "windows")]
 use os::windows as imp;
 
+#[cfg(feature = "clipboard-image")]
+pub use clipboard::ClipboardImage;
+pub use cursor::CustomCursor;
 pub use error::Error;
+pub use event::{Event, PenState, ScrollSource, TimedEvent, Touch, TouchPhase, WindowSize};
+#[cfg(feature = "gamepad")]
+pub use gamepad::{Gamepad, GamepadAxis, GamepadButton};
 pub use icon::Icon;
-pub use key::Key;
+pub use key::{Key, Modifiers, Scancode};
+pub use monitor::Monitor;
 pub use raw_window_handle::HasWindowHandle;
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -57,9 +71,10 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// much higher resolution which would result in that the window is very small.
 #[derive(Clone, Copy, Debug)]
 pub enum Scale {
-    /// This mode checks your current screen resolution and will calculate the largest window size
-    /// that can be used within that limit and resize it. Useful if you have a small buffer to
-    /// display on a high resolution screen.
+    /// This mode checks the resolution of the monitor with the mouse pointer and will calculate
+    /// the largest window size that can be used within that limit and resize it. Useful if you
+    /// have a small buffer to display on a high resolution screen. On Wayland the scaling is left
+    /// to the compositor.
     FitScreen,
     /// 1X scale (which means leave the coordinates sent into Window::new untouched)
     X1,
@@ -157,6 +172,54 @@ pub enum CursorStyle {
     ResizeUpDown,
     /// Resize in all directions
     ResizeAll,
+    /// The application is busy and can't be interacted with
+    Wait,
+    /// The application is busy but can still be interacted with
+    Progress,
+    /// The action isn't allowed
+    NotAllowed,
+    /// Pointing hand, usually used for links
+    PointingHand,
+    /// Resizing in the up-right/down-left (north-east/south-west) diagonal direction
+    ResizeNeSw,
+    /// Resizing in the up-left/down-right (north-west/south-east) diagonal direction
+    ResizeNwSe,
+    /// Resizing the top edge
+    ResizeTop,
+    /// Resizing the bottom edge
+    ResizeBottom,
+    /// Resizing the left edge
+    ResizeLeft,
+    /// Resizing the right edge
+    ResizeRight,
+    /// Resizing the top left corner
+    ResizeTopLeft,
+    /// Resizing the top right corner
+    ResizeTopRight,
+    /// Resizing the bottom left corner
+    ResizeBottomLeft,
+    /// Resizing the bottom right corner
+    ResizeBottomRight,
+    /// Zooming in, may use the default arrow on unsupported OSes.
+    ZoomIn,
+    /// Zooming out, may use the default arrow on unsupported OSes.
+    ZoomOut,
+    /// Help is available
+    Help,
+    /// Something can be moved
+    Move,
+}
+
+/// How the mouse cursor is captured by the window, see `Window::set_cursor_grab`
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum CursorGrab {
+    /// The cursor moves freely (default)
+    None,
+    /// The cursor can't leave the window
+    Confined,
+    /// The cursor stays where it is and only relative motion (`Window::get_mouse_delta`) is
+    /// reported. Useful for first-person controls.
+    Locked,
 }
 
 /// This trait can be implemented and set with ```set_input_callback``` to receive a callback
@@ -170,6 +233,13 @@ pub trait InputCallback {
     /// key in the `state` argument, as well as the translated key in the `key` argument.
     /// This includes control characters such as `Key::LeftShift`.
     fn set_key_state(&mut self, _key: Key, _state: bool) {}
+
+    /// Same as `set_key_state` but also passes in the modifiers that are active after the key
+    /// has been processed. The default implementation calls `set_key_state`.
+    /// The lock modifiers (Caps Lock, Num Lock) are currently only reported on Linux/BSD.
+    fn set_key_state_with_modifiers(&mut self, key: Key, state: bool, _modifiers: Modifiers) {
+        self.set_key_state(key, state);
+    }
 }
 
 /// Window is used to open up a window. It's possible to optionally display a 32-bit buffer when
@@ -210,6 +280,30 @@ pub enum ScaleMode {
     UpperLeft,
 }
 
+/// Fullscreen modes used with `Window::set_fullscreen` and `WindowOptions::fullscreen`. The
+/// buffer is displayed in the fullscreen window according to the `ScaleMode`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fullscreen {
+    /// A normal window
+    Off,
+    /// The window covers a monitor without changing its video mode. The monitor is given by its
+    /// index in `Window::monitors`, `None` uses the monitor the window is on.
+    Borderless(Option<usize>),
+    /// The video mode of the monitor the window is on is changed to the given one while the
+    /// window is fullscreen. Wayland doesn't allow changing video modes so this is the same as
+    /// `Borderless(None)` there.
+    Exclusive(VideoMode),
+}
+
+/// A video mode (resolution and refresh rate) of a monitor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VideoMode {
+    pub width: u32,
+    pub height: u32,
+    /// Refresh rate in millihertz (60 Hz is 60000), 0 picks the highest one available
+    pub refresh_rate: u32,
+}
+
 /// WindowOptions is creation settings for the window. By default the settings are defined for
 /// displaying a 32-bit buffer (no scaling of window is possible)
 #[derive(Clone, Copy, Debug)]
@@ -225,6 +319,7 @@ pub struct WindowOptions {
     /// Adjust how the scaling of the buffer used with update_with_buffer should be done.
     pub scale_mode: ScaleMode,
     /// Should the window be the topmost window (default: false)
+    /// Creating the window fails if it can't be kept on top, see `Window::topmost`
     pub topmost: bool,
     /// Specifies whether or not the window is allowed to draw transparent pixels (default: false)
     /// Requires borderless to be 'true'
@@ -235,6 +330,14 @@ pub struct WindowOptions {
     /// Should be mutually exclusive to resize, automatically assumes borderless.
     /// Not supported on OSX.
     pub none: bool,
+    /// If set pressing the close button of the window will not close the window. Instead
+    /// `Window::close_requested` returns true and it's up to the application to call
+    /// `Window::close` or `Window::cancel_close` (default: false)
+    /// Currently only implemented on Linux/BSD (X11 and Wayland)
+    pub intercept_close: bool,
+    /// Opens the window in a fullscreen mode (default: Fullscreen::Off)
+    /// Currently only implemented on Linux/BSD (X11 and Wayland)
+    pub fullscreen: Fullscreen,
 }
 
 #[allow(dead_code)]
@@ -524,7 +627,9 @@ impl Window {
     }
 
     /// Makes the window the topmost window and makes it stay always on top. This is useful if you
-    /// want the window to float above all over windows
+    /// want the window to float above all over windows. Returns an error on Wayland where normal
+    /// windows can't be kept on top (only the layer shell protocol provides that) and on X11 if
+    /// the window manager doesn't support it.
     ///
     /// # Examples
     ///
@@ -532,10 +637,12 @@ impl Window {
     /// # use minifb::*;
     /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
     /// // Makes the window always on top
-    /// window.topmost(true);
+    /// if let Err(err) = window.topmost(true) {
+    ///     println!("The window can't be kept on top: {}", err);
+    /// }
     /// ```
     #[inline]
-    pub fn topmost(&self, topmost: bool) {
+    pub fn topmost(&self, topmost: bool) -> Result<()> {
         self.0.topmost(topmost)
     }
 
@@ -667,6 +774,9 @@ impl Window {
     /// Get the current position of the mouse relative to the current window
     /// The coordinate system is as 0, 0 as the upper left corner
     ///
+    /// This is the position at the last `update`. On Linux and BSD all the positions in
+    /// between are reported (with timestamps) as `Event::MouseMove`, see `poll_events`.
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -850,7 +960,8 @@ impl Window {
     }
 
     /// Sets the delay for when a key is being held before it starts being repeated the default
-    /// value is 0.25 sec
+    /// value is 0.25 sec. On Linux the default comes from the X server or the compositor and the
+    /// repeats are also reported as `Event::KeyDown` with `repeat` set.
     ///
     /// # Examples
     ///
@@ -865,7 +976,7 @@ impl Window {
     }
 
     /// Sets the rate in between when the keys has passed the initial repeat_delay. The default
-    /// value is 0.05 sec
+    /// value is 0.05 sec, on Linux it comes from the X server or the compositor.
     ///
     /// # Examples
     ///
@@ -959,6 +1070,631 @@ impl Window {
     }
 }
 
+#[cfg(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+impl Window {
+    /// Returns the events that has been received during the last call to `update` (or
+    /// `update_with_buffer`) in the order they happened. Unlike the polling functions (such as
+    /// `get_keys` or `get_mouse_down`) no presses are lost if a key is pressed and released
+    /// between two updates. The events are kept until the next update.
+    ///
+    /// Currently only available on Linux/BSD (X11 and Wayland)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// window.update();
+    ///
+    /// for timed_event in window.poll_events() {
+    ///     match timed_event.event {
+    ///         Event::KeyDown { key, .. } => println!("{:?} pressed", key),
+    ///         Event::MouseMove { x, y, .. } => println!("mouse at {} {}", x, y),
+    ///         _ => (),
+    ///     }
+    /// }
+    /// ```
+    #[inline]
+    pub fn poll_events(&self) -> std::slice::Iter<'_, TimedEvent> {
+        self.0.poll_events()
+    }
+
+    /// Same as `poll_events` but removes the events from the window so they can be consumed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// window.update();
+    ///
+    /// let events: Vec<TimedEvent> = window.drain_events().collect();
+    /// ```
+    #[inline]
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, TimedEvent> {
+        self.0.drain_events()
+    }
+
+    /// Sets a callback that is called as soon as the window has been resized (during `update`)
+    /// with the new size. This allows to reallocate the buffer used with `update_with_buffer`
+    /// without having to check `get_size` every frame. The same size is also reported with
+    /// `Event::Resize`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// window.set_resize_callback(Box::new(|size: WindowSize| {
+    ///     println!("new buffer size {} x {}", size.logical_width, size.logical_height);
+    /// }));
+    /// ```
+    #[inline]
+    pub fn set_resize_callback(&mut self, callback: Box<dyn FnMut(WindowSize)>) {
+        self.0.set_resize_callback(callback)
+    }
+
+    /// Returns true if the user has asked for the window to be closed (for example by pressing
+    /// the close button) and the window was created with `WindowOptions::intercept_close`. The
+    /// window stays open until `close` is called. Use `cancel_close` to ignore the request.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # fn ask_user_to_save() -> bool { true }
+    /// let mut window = Window::new("Test", 640, 400,
+    ///     WindowOptions {
+    ///        intercept_close: true,
+    ///        ..WindowOptions::default()
+    ///  })
+    ///  .expect("Unable to open Window");
+    ///
+    /// while window.is_open() {
+    ///     window.update();
+    ///
+    ///     if window.close_requested() {
+    ///         if ask_user_to_save() {
+    ///             window.close();
+    ///         } else {
+    ///             window.cancel_close();
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    #[inline]
+    pub fn close_requested(&self) -> bool {
+        self.0.close_requested()
+    }
+
+    /// Ignores a pending close request. See `close_requested`
+    #[inline]
+    pub fn cancel_close(&mut self) {
+        self.0.cancel_close()
+    }
+
+    /// Closes the window, after this `is_open` will return false.
+    #[inline]
+    pub fn close(&mut self) {
+        self.0.close()
+    }
+
+    /// Returns the keyboard modifiers (Shift, Ctrl, Caps Lock, etc) that are currently active.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// if window.get_modifiers().contains(Modifiers::CTRL) && window.is_key_pressed(Key::S, KeyRepeat::No) {
+    ///     println!("save");
+    /// }
+    /// ```
+    #[inline]
+    pub fn get_modifiers(&self) -> Modifiers {
+        self.0.get_modifiers()
+    }
+
+    /// Sets the position of the text cursor (in buffer coordinates) so the input method (IME)
+    /// can place its candidate window next to the text being composed. See `Event::Preedit`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// // the text field is at (10, 20) and the cursor is after 4 characters 8 pixels wide
+    /// window.set_ime_position(10 + 4 * 8, 20);
+    /// ```
+    #[inline]
+    pub fn set_ime_position(&mut self, x: usize, y: usize) {
+        self.0.set_ime_position(x, y)
+    }
+
+    /// Puts text on the clipboard. The window keeps the text and hands it out when other
+    /// applications asks for it, so this only works as long as the window is open and `update` (or
+    /// `update_with_buffer`) is called.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// window.set_clipboard_text("Hello clipboard!");
+    /// ```
+    #[inline]
+    pub fn set_clipboard_text(&mut self, text: &str) {
+        self.0.set_clipboard_text(text)
+    }
+
+    /// Returns the text on the clipboard or `None` if the clipboard is empty or doesn't contain
+    /// text. This waits for the application owning the clipboard to send the text.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// if let Some(text) = window.get_clipboard_text() {
+    ///     println!("pasted: {}", text);
+    /// }
+    /// ```
+    #[inline]
+    pub fn get_clipboard_text(&mut self) -> Option<String> {
+        self.0.get_clipboard_text()
+    }
+
+    /// Puts an image on the clipboard. The buffer uses the same layout as `update_with_buffer`
+    /// but the upper 8 bits are used as alpha (see `ClipboardImage`). Like with
+    /// `set_clipboard_text` the image is only available while the window is open. Only
+    /// available with the `clipboard-image` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// // an opaque red 64 x 64 image
+    /// let buffer = vec![0xffff0000; 64 * 64];
+    /// window.set_clipboard_image(&buffer, 64, 64).unwrap();
+    /// ```
+    #[cfg(feature = "clipboard-image")]
+    #[inline]
+    pub fn set_clipboard_image(
+        &mut self,
+        buffer: &[u32],
+        width: usize,
+        height: usize,
+    ) -> Result<()> {
+        self.0.set_clipboard_image(buffer, width, height)
+    }
+
+    /// Returns the image on the clipboard or `None` if the clipboard is empty or doesn't contain
+    /// an image. This waits for the application owning the clipboard to send the image. Only
+    /// available with the `clipboard-image` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// if let Some(image) = window.get_clipboard_image() {
+    ///     println!("pasted a {} x {} image", image.width, image.height);
+    /// }
+    /// ```
+    #[cfg(feature = "clipboard-image")]
+    #[inline]
+    pub fn get_clipboard_image(&mut self) -> Option<ClipboardImage> {
+        self.0.get_clipboard_image()
+    }
+
+    /// Sets the primary selection, which is pasted with the middle mouse button. Applications
+    /// usually set it to the text that is selected (highlighted). Like with
+    /// `set_clipboard_text` the text is only available while the window is open.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// window.set_primary_selection("selected text");
+    /// ```
+    #[inline]
+    pub fn set_primary_selection(&mut self, text: &str) {
+        self.0.set_primary_selection(text)
+    }
+
+    /// Returns the text of the primary selection, see `set_primary_selection`. Usually called
+    /// when the middle mouse button is pressed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// if window.get_mouse_down(MouseButton::Middle) {
+    ///     if let Some(text) = window.get_primary_selection() {
+    ///         println!("pasted: {}", text);
+    ///     }
+    /// }
+    /// ```
+    #[inline]
+    pub fn get_primary_selection(&mut self) -> Option<String> {
+        self.0.get_primary_selection()
+    }
+
+    /// Replaces the cursor with a custom image while it's over the window. Use
+    /// `set_cursor_style` to go back to one of the standard cursors.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// // a red 5 x 5 brush
+    /// let cursor = CustomCursor::from_argb(5, 5, (2, 2), &[0xffff_0000; 5 * 5]).unwrap();
+    /// window.set_custom_cursor(&cursor);
+    /// ```
+    #[inline]
+    pub fn set_custom_cursor(&mut self, cursor: &CustomCursor) {
+        self.0.set_custom_cursor(cursor)
+    }
+
+    /// Captures the mouse cursor. With `CursorGrab::Confined` the cursor can't leave the window
+    /// and with `CursorGrab::Locked` it stays in place, which together with `get_mouse_delta`
+    /// allows unbounded mouse-look. The cursor is still visible, use `set_cursor_visibility` to
+    /// hide it. The grab is released while the window doesn't have focus.
+    ///
+    /// Returns an error if the pointer can't be grabbed or, on Wayland, if the compositor
+    /// doesn't support pointer constraints.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// window.set_cursor_visibility(false);
+    /// window.set_cursor_grab(CursorGrab::Locked).unwrap();
+    /// ```
+    #[inline]
+    pub fn set_cursor_grab(&mut self, grab: CursorGrab) -> Result<()> {
+        self.0.set_cursor_grab(grab)
+    }
+
+    /// Returns how far the mouse moved since the previous `update`. Unlike the difference
+    /// between two `get_mouse_pos` calls this isn't stopped by the window or screen edges and
+    /// keeps working when the cursor is locked with `set_cursor_grab`. The motion is in
+    /// unaccelerated device units (usually close to pixels) and isn't affected by the window
+    /// `Scale`. Only motion while the window has focus is reported.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// # let (mut yaw, mut pitch) = (0.0, 0.0);
+    /// let (dx, dy) = window.get_mouse_delta();
+    /// yaw += dx * 0.1;
+    /// pitch += dy * 0.1;
+    /// ```
+    #[inline]
+    pub fn get_mouse_delta(&self) -> (f32, f32) {
+        self.0.get_mouse_delta()
+    }
+
+    /// Moves the mouse cursor to a position in the window. The position is in buffer
+    /// coordinates (same as `MouseMode::Pass`) so it's multiplied by the window `Scale`.
+    ///
+    /// On Wayland the cursor can only be moved when the compositor supports pointer
+    /// constraints and the window has the pointer focus, an error is returned otherwise. The
+    /// cursor is moved during a later `update`, once the compositor has accepted the move.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// // center the cursor
+    /// window.set_mouse_pos(320.0, 200.0).unwrap();
+    /// ```
+    #[inline]
+    pub fn set_mouse_pos(&mut self, x: f32, y: f32) -> Result<()> {
+        self.0.set_mouse_pos(x, y)
+    }
+
+    /// Returns the fingers currently touching the window, as of the latest `update`. Use the
+    /// `Event::Touch` events to get every movement and to see touches that began and ended
+    /// between two updates.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// for touch in window.get_touches() {
+    ///     println!("finger {} at {}, {}", touch.id, touch.x, touch.y);
+    /// }
+    /// ```
+    #[inline]
+    pub fn get_touches(&self) -> &[Touch] {
+        self.0.get_touches()
+    }
+
+    /// Returns the state of the drawing tablet pen over the window, as of the latest `update`,
+    /// or `None` if there is no pen over the window. Use the `Event::Pen` events to get every
+    /// movement of the pen. The pen moves the mouse as well, so applications that doesn't care
+    /// about pressure and tilt can keep using the mouse functions.
+    ///
+    /// On X11 the windowing system doesn't report when the pen is moved away from the tablet,
+    /// so the pen is considered to be over the window until the pointer leaves it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// if let Some(pen) = window.get_pen_state() {
+    ///     if pen.in_contact && !pen.eraser {
+    ///         println!("brush of size {} at {}, {}", pen.pressure * 10.0, pen.x, pen.y);
+    ///     }
+    /// }
+    /// ```
+    #[inline]
+    pub fn get_pen_state(&self) -> Option<PenState> {
+        self.0.get_pen_state()
+    }
+
+    /// Returns the connected gamepads, as of the latest `update`. Use the gamepad events to get
+    /// every button press and to know when gamepads are connected or disconnected. Gamepads
+    /// that are connected when the window is created are reported on the first `update`.
+    ///
+    /// The gamepads are read from the evdev devices in `/dev/input` which the user needs
+    /// permission to read (this is usually the case for the user logged in at the computer).
+    /// Only available on Linux with the `gamepad` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// for gamepad in window.get_gamepads() {
+    ///     if gamepad.is_button_down(GamepadButton::South) {
+    ///         println!("{} jumps", gamepad.name());
+    ///     }
+    ///     let speed = gamepad.get_axis(GamepadAxis::LeftStickX) * 4.0;
+    /// }
+    /// ```
+    #[cfg(all(feature = "gamepad", target_os = "linux"))]
+    #[inline]
+    pub fn get_gamepads(&self) -> &[Gamepad] {
+        self.0.get_gamepads()
+    }
+
+    /// Returns the click count of a mouse button while it's held down: 1 for a single click, 2
+    /// for a double click and so on, or 0 if the button is up. A press counts as another click
+    /// when it happens within the double click interval of the desktop (or the one set with
+    /// `set_default_double_click_interval`) and close to the previous press.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// if window.get_click_count(MouseButton::Left) == 2 {
+    ///     println!("double click");
+    /// }
+    /// ```
+    #[inline]
+    pub fn get_click_count(&self, button: MouseButton) -> u32 {
+        self.0.get_click_count(button)
+    }
+
+    /// Sets the double click interval used when the desktop doesn't have one. The desktop
+    /// setting is only available on X11 (from XSettings), the default is 400 ms.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # use std::time::Duration;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// window.set_default_double_click_interval(Duration::from_millis(500));
+    /// ```
+    #[inline]
+    pub fn set_default_double_click_interval(&mut self, interval: Duration) {
+        self.0.set_default_double_click_interval(interval)
+    }
+
+    /// Switches the window to or from fullscreen. Returns an error if the monitor or the video
+    /// mode isn't available.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// // scale the 320x240 buffer to the screen while keeping the aspect ratio
+    /// let options = WindowOptions {
+    ///     scale_mode: ScaleMode::AspectRatioStretch,
+    ///     ..WindowOptions::default()
+    /// };
+    /// let mut window = Window::new("Test", 320, 240, options).unwrap();
+    /// window.set_fullscreen(Fullscreen::Borderless(None)).unwrap();
+    /// ```
+    #[inline]
+    pub fn set_fullscreen(&mut self, fullscreen: Fullscreen) -> Result<()> {
+        self.0.set_fullscreen(fullscreen)
+    }
+
+    /// Resizes the window, the size is the same as the one returned by `get_size`. This also
+    /// works for windows that the user can't resize. `Event::Resize` is sent when the size has
+    /// changed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// window.set_size(1280, 800);
+    /// ```
+    #[inline]
+    pub fn set_size(&mut self, width: usize, height: usize) {
+        self.0.set_size(width, height)
+    }
+
+    /// Sets the smallest size the user can resize the window to, or removes the limit with
+    /// `None`. Only used for windows created with `WindowOptions::resize`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let options = WindowOptions { resize: true, ..WindowOptions::default() };
+    /// # let mut window = Window::new("Test", 640, 400, options).unwrap();
+    /// window.set_min_size(Some((320, 200)));
+    /// window.set_max_size(None);
+    /// ```
+    #[inline]
+    pub fn set_min_size(&mut self, size: Option<(usize, usize)>) {
+        self.0.set_min_size(size)
+    }
+
+    /// Sets the largest size the user can resize the window to, or removes the limit with
+    /// `None`. Only used for windows created with `WindowOptions::resize`.
+    #[inline]
+    pub fn set_max_size(&mut self, size: Option<(usize, usize)>) {
+        self.0.set_max_size(size)
+    }
+
+    /// Keeps the aspect ratio (width, height) of the window when the user resizes it, or lets it
+    /// change freely with `None`. Not used while the window is maximized or fullscreen.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let options = WindowOptions { resize: true, ..WindowOptions::default() };
+    /// # let mut window = Window::new("Test", 640, 360, options).unwrap();
+    /// window.set_aspect_ratio(Some((16, 9)));
+    /// ```
+    #[inline]
+    pub fn set_aspect_ratio(&mut self, ratio: Option<(usize, usize)>) {
+        self.0.set_aspect_ratio(ratio)
+    }
+
+    /// Returns the monitors connected to the computer. The index of a monitor in the list is the
+    /// one used with `Fullscreen::Borderless`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// for monitor in window.monitors() {
+    ///     let (width, height) = monitor.size();
+    ///     println!("{}: {}x{}", monitor.name(), width, height);
+    /// }
+    /// ```
+    #[inline]
+    pub fn monitors(&self) -> Vec<Monitor> {
+        self.0.monitors()
+    }
+
+    /// Returns the monitor the window is on (the one with the center of the window on X11), or
+    /// `None` if it isn't known yet.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// if let Some(monitor) = window.current_monitor() {
+    ///     window.set_target_fps(monitor.refresh_rate() as usize / 1000);
+    /// }
+    /// ```
+    #[inline]
+    pub fn current_monitor(&self) -> Option<Monitor> {
+        self.0.current_monitor()
+    }
+
+    /// Maximizes the window or restores it from being maximized. `Event::Maximized` is sent
+    /// when the window manager has changed the state.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// let maximized = window.is_maximized();
+    /// window.set_maximized(!maximized);
+    /// ```
+    #[inline]
+    pub fn set_maximized(&mut self, maximized: bool) {
+        self.0.set_maximized(maximized)
+    }
+
+    /// Minimizes the window or restores it from being minimized. Wayland doesn't allow a window
+    /// to restore itself, so only minimizing works there.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// window.set_minimized(true);
+    /// ```
+    #[inline]
+    pub fn set_minimized(&mut self, minimized: bool) {
+        self.0.set_minimized(minimized)
+    }
+
+    /// Check if the window is maximized
+    #[inline]
+    pub fn is_maximized(&self) -> bool {
+        self.0.is_maximized()
+    }
+
+    /// Check if the window is minimized. Always false on Wayland, which doesn't report it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// // no need to draw while minimized
+    /// if !window.is_minimized() {
+    ///     // draw...
+    /// }
+    /// ```
+    #[inline]
+    pub fn is_minimized(&self) -> bool {
+        self.0.is_minimized()
+    }
+
+    /// Check if a physical key is down. Unlike `is_key_down` this doesn't depend on the keyboard
+    /// layout, so `Scancode::W` is the key above `Scancode::S` on AZERTY and Dvorak keyboards as
+    /// well.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use minifb::*;
+    /// # let mut window = Window::new("Test", 640, 400, WindowOptions::default()).unwrap();
+    /// if window.is_scancode_down(Scancode::W) {
+    ///     println!("moving forward");
+    /// }
+    /// ```
+    #[inline]
+    pub fn is_scancode_down(&self, scancode: Scancode) -> bool {
+        self.0.is_scancode_down(scancode)
+    }
+}
+
 /// Command key on Mac OS
 pub const MENU_KEY_COMMAND: usize = 1;
 /// Windows key on Windows
@@ -1218,6 +1954,8 @@ impl Default for WindowOptions {
             scale_mode: ScaleMode::Stretch,
             topmost: false,
             none: false,
+            intercept_close: false,
+            fullscreen: Fullscreen::Off,
         }
     }
 }
@@ -0,0 +1,52 @@
+/// A monitor connected to the computer, as returned by `Window::monitors`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    not(any(
+        target_os = "linux",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )),
+    allow(dead_code)
+)]
+pub struct Monitor {
+    pub(crate) name: String,
+    pub(crate) position: (i32, i32),
+    pub(crate) size: (usize, usize),
+    pub(crate) refresh_rate: u32,
+    pub(crate) scale_factor: f32,
+}
+
+impl Monitor {
+    /// The name of the monitor, which is the name of the connector (such as "DP-1") on Linux.
+    /// Empty if it isn't known.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Position of the upper left corner of the monitor on the desktop
+    #[inline]
+    pub fn position(&self) -> (i32, i32) {
+        self.position
+    }
+
+    /// Resolution of the monitor in pixels
+    #[inline]
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    /// Refresh rate in millihertz (60 Hz is 60000), 0 if it isn't known
+    #[inline]
+    pub fn refresh_rate(&self) -> u32 {
+        self.refresh_rate
+    }
+
+    /// How much the desktop scales things up on this monitor, 2.0 on a typical HiDPI setup
+    #[inline]
+    pub fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+}
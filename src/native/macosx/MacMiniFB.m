@@ -73,6 +73,24 @@ enum CursorStyle {
     CursorStyle_ResizeLeftRight,
     CursorStyle_ResizeUpDown,
     CursorStyle_SizeAll,
+    CursorStyle_Wait,
+    CursorStyle_Progress,
+    CursorStyle_NotAllowed,
+    CursorStyle_PointingHand,
+    CursorStyle_ResizeNeSw,
+    CursorStyle_ResizeNwSe,
+    CursorStyle_ResizeTop,
+    CursorStyle_ResizeBottom,
+    CursorStyle_ResizeLeft,
+    CursorStyle_ResizeRight,
+    CursorStyle_ResizeTopLeft,
+    CursorStyle_ResizeTopRight,
+    CursorStyle_ResizeBottomLeft,
+    CursorStyle_ResizeBottomRight,
+    CursorStyle_ZoomIn,
+    CursorStyle_ZoomOut,
+    CursorStyle_Help,
+    CursorStyle_Move,
     CursorStyle_Count,
 };
 
@@ -89,6 +107,25 @@ void cursor_init() {
     s_cursors[CursorStyle_ResizeLeftRight] = [[NSCursor resizeLeftRightCursor] retain];
     s_cursors[CursorStyle_ResizeUpDown] = [[NSCursor resizeUpDownCursor] retain];
     s_cursors[CursorStyle_SizeAll] = [[NSCursor closedHandCursor] retain];
+    // macOS has no public busy, diagonal resize, zoom or help cursors so these use the closest match
+    s_cursors[CursorStyle_Wait] = [[NSCursor arrowCursor] retain];
+    s_cursors[CursorStyle_Progress] = [[NSCursor arrowCursor] retain];
+    s_cursors[CursorStyle_NotAllowed] = [[NSCursor operationNotAllowedCursor] retain];
+    s_cursors[CursorStyle_PointingHand] = [[NSCursor pointingHandCursor] retain];
+    s_cursors[CursorStyle_ResizeNeSw] = [[NSCursor crosshairCursor] retain];
+    s_cursors[CursorStyle_ResizeNwSe] = [[NSCursor crosshairCursor] retain];
+    s_cursors[CursorStyle_ResizeTop] = [[NSCursor resizeUpCursor] retain];
+    s_cursors[CursorStyle_ResizeBottom] = [[NSCursor resizeDownCursor] retain];
+    s_cursors[CursorStyle_ResizeLeft] = [[NSCursor resizeLeftCursor] retain];
+    s_cursors[CursorStyle_ResizeRight] = [[NSCursor resizeRightCursor] retain];
+    s_cursors[CursorStyle_ResizeTopLeft] = [[NSCursor crosshairCursor] retain];
+    s_cursors[CursorStyle_ResizeTopRight] = [[NSCursor crosshairCursor] retain];
+    s_cursors[CursorStyle_ResizeBottomLeft] = [[NSCursor crosshairCursor] retain];
+    s_cursors[CursorStyle_ResizeBottomRight] = [[NSCursor crosshairCursor] retain];
+    s_cursors[CursorStyle_ZoomIn] = [[NSCursor arrowCursor] retain];
+    s_cursors[CursorStyle_ZoomOut] = [[NSCursor arrowCursor] retain];
+    s_cursors[CursorStyle_Help] = [[NSCursor arrowCursor] retain];
+    s_cursors[CursorStyle_Move] = [[NSCursor openHandCursor] retain];
 }
 
 ///////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -437,8 +437,9 @@ impl Window {
     }
 
     #[inline]
-    pub fn topmost(&self, topmost: bool) {
-        unsafe { mfb_topmost(self.window_handle, topmost) }
+    pub fn topmost(&self, topmost: bool) -> Result<()> {
+        unsafe { mfb_topmost(self.window_handle, topmost) };
+        Ok(())
     }
 
     #[inline]
@@ -1,4 +1,296 @@
-use crate::{Key, MenuHandle, MenuItem, MenuItemHandle, Result, UnixMenu, UnixMenuItem};
+#[cfg(feature = "clipboard-image")]
+use crate::ClipboardImage;
+use crate::{
+    Key, MenuHandle, MenuItem, MenuItemHandle, MouseButton, Result, UnixMenu, UnixMenuItem,
+};
+use std::{ffi::OsStr, os::unix::ffi::OsStrExt, path::PathBuf};
+
+/// Mime types used for text on the clipboard, in order of preference. `UTF8_STRING` is what
+/// X11 applications use and is offered by many Wayland applications as well.
+pub const TEXT_MIME_TYPES: [&str; 3] = ["text/plain;charset=utf-8", "UTF8_STRING", "text/plain"];
+
+/// The selections that data can be put on and read from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Selection {
+    /// The regular clipboard (copy and paste)
+    Clipboard,
+    /// The primary selection (select to copy and middle click to paste)
+    Primary,
+}
+
+/// Data put on the clipboard as (mime type, data) pairs, one for each supported format
+pub type ClipboardData = Vec<(&'static str, Vec<u8>)>;
+
+/// Mime type used for images on the clipboard
+#[cfg(feature = "clipboard-image")]
+pub const IMAGE_MIME_TYPE: &str = "image/png";
+
+/// Cursor theme names for each `CursorStyle` (indexed by the style) in order of preference.
+/// Themes differ in which names they have so the standard (CSS) names are tried together with
+/// the legacy X11 and other common names. The arrow is used if none of them are found.
+pub const CURSOR_NAMES: [&[&str]; 26] = [
+    // Arrow
+    &["arrow", "default", "left_ptr"],
+    // Ibeam
+    &["xterm", "text", "ibeam"],
+    // Crosshair
+    &["crosshair", "cross", "tcross"],
+    // ClosedHand
+    &["hand2", "grabbing", "closedhand", "hand1"],
+    // OpenHand
+    &["hand2", "grab", "openhand", "hand1"],
+    // ResizeLeftRight
+    &[
+        "sb_h_double_arrow",
+        "ew-resize",
+        "col-resize",
+        "h_double_arrow",
+        "size_hor",
+    ],
+    // ResizeUpDown
+    &[
+        "sb_v_double_arrow",
+        "ns-resize",
+        "row-resize",
+        "v_double_arrow",
+        "size_ver",
+    ],
+    // ResizeAll
+    &["diamond_cross", "all-scroll", "fleur", "move", "size_all"],
+    // Wait
+    &["wait", "watch"],
+    // Progress
+    &["progress", "left_ptr_watch", "half-busy", "wait", "watch"],
+    // NotAllowed
+    &[
+        "not-allowed",
+        "crossed_circle",
+        "forbidden",
+        "no-drop",
+        "circle",
+    ],
+    // PointingHand
+    &["pointer", "pointing_hand", "hand2", "hand1"],
+    // ResizeNeSw
+    &["nesw-resize", "size_bdiag", "fd_double_arrow"],
+    // ResizeNwSe
+    &["nwse-resize", "size_fdiag", "bd_double_arrow"],
+    // ResizeTop
+    &["n-resize", "top_side", "ns-resize", "sb_v_double_arrow"],
+    // ResizeBottom
+    &["s-resize", "bottom_side", "ns-resize", "sb_v_double_arrow"],
+    // ResizeLeft
+    &["w-resize", "left_side", "ew-resize", "sb_h_double_arrow"],
+    // ResizeRight
+    &["e-resize", "right_side", "ew-resize", "sb_h_double_arrow"],
+    // ResizeTopLeft
+    &["nw-resize", "top_left_corner", "nwse-resize", "size_fdiag"],
+    // ResizeTopRight
+    &["ne-resize", "top_right_corner", "nesw-resize", "size_bdiag"],
+    // ResizeBottomLeft
+    &[
+        "sw-resize",
+        "bottom_left_corner",
+        "nesw-resize",
+        "size_bdiag",
+    ],
+    // ResizeBottomRight
+    &[
+        "se-resize",
+        "bottom_right_corner",
+        "nwse-resize",
+        "size_fdiag",
+    ],
+    // ZoomIn
+    &["zoom-in", "zoom_in"],
+    // ZoomOut
+    &["zoom-out", "zoom_out"],
+    // Help
+    &["help", "question_arrow", "whats_this", "left_ptr_help"],
+    // Move
+    &["move", "fleur", "all-scroll", "size_all"],
+];
+
+/// Counts the clicks of the mouse buttons, see `Window::get_click_count`. The times are in
+/// milliseconds (same as the windowing system events) and the positions in window pixels.
+pub struct ClickCounter {
+    /// Max time between two presses of a multi-click as set by the desktop
+    pub desktop_interval: Option<u32>,
+    /// Used when the desktop doesn't have a double click time
+    pub default_interval: u32,
+    /// Max distance between two presses of a multi-click
+    pub distance: f32,
+    // button, time and position of the previous press
+    last_press: Option<(MouseButton, u32, f32, f32)>,
+    count: u32,
+    // click count of the buttons that are down
+    down: [u32; 5],
+}
+
+impl ClickCounter {
+    pub fn new() -> ClickCounter {
+        ClickCounter {
+            desktop_interval: None,
+            // same defaults as GTK
+            default_interval: 400,
+            distance: 5.0,
+            last_press: None,
+            count: 0,
+            down: [0; 5],
+        }
+    }
+
+    /// Returns the click count of a button press
+    pub fn press(&mut self, button: MouseButton, time: u32, x: f32, y: f32) -> u32 {
+        let interval = self.desktop_interval.unwrap_or(self.default_interval);
+
+        self.count = match self.last_press {
+            Some((last_button, last_time, last_x, last_y))
+                if last_button == button
+                    && time.wrapping_sub(last_time) <= interval
+                    && (x - last_x).abs() <= self.distance
+                    && (y - last_y).abs() <= self.distance =>
+            {
+                self.count + 1
+            }
+            _ => 1,
+        };
+
+        self.last_press = Some((button, time, x, y));
+        self.down[button as usize] = self.count;
+        self.count
+    }
+
+    /// Returns the click count of the press that the release ends
+    #[inline]
+    pub fn release(&mut self, button: MouseButton) -> u32 {
+        std::mem::take(&mut self.down[button as usize])
+    }
+
+    #[inline]
+    pub fn get(&self, button: MouseButton) -> u32 {
+        self.down[button as usize]
+    }
+}
+
+/// Converts a `0xAARRGGBB` pixel to premultiplied alpha, as used by cursor images
+pub fn premultiply_alpha(pixel: u32) -> u32 {
+    let alpha = pixel >> 24;
+    let premultiply = |shift: u32| (((pixel >> shift) & 0xff) * alpha / 255) << shift;
+
+    (alpha << 24) | premultiply(16) | premultiply(8) | premultiply(0)
+}
+
+/// Distance of a mouse wheel click, this is what libinput and most compositors uses
+pub const PIXELS_PER_NOTCH: f32 = 15.0;
+
+/// Mime type used for the files being dragged and dropped
+pub const URI_LIST_MIME_TYPE: &str = "text/uri-list";
+
+/// Parses a `text/uri-list` (RFC 2483) into the paths of the local files in it
+pub fn parse_uri_list(uri_list: &[u8]) -> Vec<PathBuf> {
+    uri_list
+        .split(|&c| c == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+        .filter(|line| !line.is_empty() && !line.starts_with(b"#"))
+        .filter_map(|uri| uri.strip_prefix(b"file://"))
+        .filter_map(|uri| {
+            // skip the host name (usually empty or "localhost")
+            let path = &uri[uri.iter().position(|&c| c == b'/')?..];
+            Some(PathBuf::from(OsStr::from_bytes(&percent_decode(path))))
+        })
+        .collect()
+}
+
+fn percent_decode(bytes: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            // from_str_radix would also accept a sign
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match hex {
+            Some(c) if bytes[i] == b'%' => {
+                decoded.push(c);
+                i += 3;
+            }
+            _ => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    decoded
+}
+
+/// Encodes a `0xAARRGGBB` buffer as PNG for putting it on the clipboard
+#[cfg(feature = "clipboard-image")]
+pub fn encode_png(buffer: &[u32], width: usize, height: usize) -> Option<Vec<u8>> {
+    let mut png_data = Vec::new();
+
+    let mut encoder = png::Encoder::new(&mut png_data, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let rgba: Vec<u8> = buffer[..width * height]
+        .iter()
+        .flat_map(|pixel| {
+            let [a, r, g, b] = pixel.to_be_bytes();
+            [r, g, b, a]
+        })
+        .collect();
+
+    let mut writer = encoder.write_header().ok()?;
+    writer.write_image_data(&rgba).ok()?;
+    writer.finish().ok()?;
+
+    Some(png_data)
+}
+
+/// Decodes a PNG from the clipboard into a `0xAARRGGBB` buffer
+#[cfg(feature = "clipboard-image")]
+pub fn decode_png(png_data: &[u8]) -> Option<ClipboardImage> {
+    let mut decoder = png::Decoder::new(png_data);
+    // makes sure we always get 8 bit RGB(A) or grayscale (with alpha) data
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+
+    let mut reader = decoder.read_info().ok()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).ok()?;
+    let buffer = &buffer[..info.buffer_size()];
+
+    let data: Vec<u32> = match info.color_type {
+        png::ColorType::Rgba => buffer
+            .chunks_exact(4)
+            .map(|p| u32::from_be_bytes([p[3], p[0], p[1], p[2]]))
+            .collect(),
+        png::ColorType::Rgb => buffer
+            .chunks_exact(3)
+            .map(|p| u32::from_be_bytes([0xff, p[0], p[1], p[2]]))
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buffer
+            .chunks_exact(2)
+            .map(|p| u32::from_be_bytes([p[1], p[0], p[0], p[0]]))
+            .collect(),
+        png::ColorType::Grayscale => buffer
+            .iter()
+            .map(|&p| u32::from_be_bytes([0xff, p, p, p]))
+            .collect(),
+        png::ColorType::Indexed => return None,
+    };
+
+    Some(ClipboardImage {
+        width: info.width as usize,
+        height: info.height as usize,
+        data,
+    })
+}
 
 pub struct Menu {
     pub internal: UnixMenu,
@@ -104,3 +396,48 @@ extern "C" {
         src_stride: u32,
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uri_list() {
+        let uri_list = b"# dragged from a file manager\r\n\
+            file:///home/user/a.txt\r\n\
+            file://localhost/home/user/b.txt\r\n\
+            file://host/tmp/c\n\
+            http://example.com/d.txt\r\n\
+            \r\n\
+            file:relative\n\
+            file:///tmp/no%20newline";
+
+        assert_eq!(
+            parse_uri_list(uri_list),
+            [
+                PathBuf::from("/home/user/a.txt"),
+                PathBuf::from("/home/user/b.txt"),
+                PathBuf::from("/tmp/c"),
+                PathBuf::from("/tmp/no newline"),
+            ]
+        );
+        assert!(parse_uri_list(b"").is_empty());
+        assert!(parse_uri_list(b"file://no-path\r\n").is_empty());
+    }
+
+    #[test]
+    fn percent_escapes() {
+        assert_eq!(
+            percent_decode(b"/a%20b%2Fc%c3%A9"),
+            "/a b/c\u{e9}".as_bytes()
+        );
+        assert_eq!(percent_decode(b"/%ff"), b"/\xff");
+        // malformed escapes are kept as they are
+        assert_eq!(percent_decode(b"/100%"), b"/100%");
+        assert_eq!(percent_decode(b"/%2"), b"/%2");
+        assert_eq!(percent_decode(b"/%zz"), b"/%zz");
+        assert_eq!(percent_decode(b"/%+5"), b"/%+5");
+        assert_eq!(percent_decode(b"/%-1"), b"/%-1");
+        assert_eq!(percent_decode(b"/%%41"), b"/%A");
+    }
+}
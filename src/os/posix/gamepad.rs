@@ -0,0 +1,847 @@
+//! Gamepads are read from the evdev devices in `/dev/input`, inotify is used to find the ones
+//! that are plugged in (or removed) while the application is running.
+
+use std::{
+    ffi::{CStr, CString},
+    fs::{File, OpenOptions},
+    io::ErrorKind,
+    mem,
+    os::{
+        raw::{c_int, c_ulong},
+        unix::{ffi::OsStrExt, fs::OpenOptionsExt, io::AsRawFd},
+    },
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    event::EventBuffer,
+    gamepad::{GAMEPAD_AXIS_COUNT, GAMEPAD_BUTTON_COUNT},
+    Event, Gamepad, GamepadAxis, GamepadButton,
+};
+
+const INPUT_DIR: &str = "/dev/input";
+
+// from linux/input-event-codes.h
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_ABS: u16 = 0x03;
+const SYN_REPORT: u16 = 0;
+const SYN_DROPPED: u16 = 3;
+const KEY_MAX: usize = 0x2ff;
+const ABS_HAT0X: u16 = 0x10;
+const ABS_HAT0Y: u16 = 0x11;
+const BTN_GAMEPAD: u16 = 0x130;
+
+const BUTTONS: [(u16, GamepadButton); GAMEPAD_BUTTON_COUNT] = [
+    (0x130, GamepadButton::South),
+    (0x131, GamepadButton::East),
+    (0x133, GamepadButton::North),
+    (0x134, GamepadButton::West),
+    (0x136, GamepadButton::LeftShoulder),
+    (0x137, GamepadButton::RightShoulder),
+    (0x138, GamepadButton::LeftTrigger),
+    (0x139, GamepadButton::RightTrigger),
+    (0x13a, GamepadButton::Select),
+    (0x13b, GamepadButton::Start),
+    (0x13c, GamepadButton::Mode),
+    (0x13d, GamepadButton::LeftStick),
+    (0x13e, GamepadButton::RightStick),
+    (0x220, GamepadButton::DPadUp),
+    (0x221, GamepadButton::DPadDown),
+    (0x222, GamepadButton::DPadLeft),
+    (0x223, GamepadButton::DPadRight),
+];
+
+const AXES: [(u16, GamepadAxis); GAMEPAD_AXIS_COUNT] = [
+    (0x00, GamepadAxis::LeftStickX),
+    (0x01, GamepadAxis::LeftStickY),
+    (0x02, GamepadAxis::LeftTrigger),
+    (0x03, GamepadAxis::RightStickX),
+    (0x04, GamepadAxis::RightStickY),
+    (0x05, GamepadAxis::RightTrigger),
+];
+
+// ioctl numbers from linux/input.h
+const fn ioc(dir: c_ulong, nr: c_ulong, size: usize) -> c_ulong {
+    const IOC_TYPE: c_ulong = b'E' as c_ulong;
+    (dir << 30) | ((size as c_ulong) << 16) | (IOC_TYPE << 8) | nr
+}
+
+const IOC_WRITE: c_ulong = 1;
+const IOC_READ: c_ulong = 2;
+
+const fn eviocgname(len: usize) -> c_ulong {
+    ioc(IOC_READ, 0x06, len)
+}
+
+const fn eviocgkey(len: usize) -> c_ulong {
+    ioc(IOC_READ, 0x18, len)
+}
+
+const fn eviocgbit(ev: u16, len: usize) -> c_ulong {
+    ioc(IOC_READ, 0x20 + ev as c_ulong, len)
+}
+
+const fn eviocgabs(abs: u16) -> c_ulong {
+    ioc(
+        IOC_READ,
+        0x40 + abs as c_ulong,
+        mem::size_of::<libc::input_absinfo>(),
+    )
+}
+
+const EVIOCSCLOCKID: c_ulong = ioc(IOC_WRITE, 0xa0, mem::size_of::<c_int>());
+
+#[inline]
+fn test_bit(bits: &[u8], bit: usize) -> bool {
+    bits[bit / 8] & (1 << (bit % 8)) != 0
+}
+
+/// An opened evdev gamepad and the gamepad reported to the user (at the same index)
+struct Device {
+    file: File,
+    path: PathBuf,
+    axes: [Option<libc::input_absinfo>; GAMEPAD_AXIS_COUNT],
+    // events are skipped until the next report after the kernel dropped some
+    dropped: bool,
+}
+
+impl Device {
+    fn open(path: &Path) -> Option<(Device, String)> {
+        let file = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK | libc::O_CLOEXEC)
+            .open(path)
+            .ok()?;
+        let fd = file.as_raw_fd();
+
+        unsafe {
+            let mut keys = [0u8; KEY_MAX / 8 + 1];
+            if libc::ioctl(fd, eviocgbit(EV_KEY, keys.len()) as _, keys.as_mut_ptr()) < 0
+                || !test_bit(&keys, BTN_GAMEPAD as usize)
+            {
+                return None;
+            }
+
+            let mut name = [0u8; 256];
+            libc::ioctl(fd, eviocgname(name.len() - 1) as _, name.as_mut_ptr());
+            let name = CStr::from_bytes_until_nul(&name)
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+
+            // use the same clock as the windowing system for the event times
+            let clock: c_int = libc::CLOCK_MONOTONIC;
+            libc::ioctl(fd, EVIOCSCLOCKID as _, &clock);
+
+            let mut axes = [None; GAMEPAD_AXIS_COUNT];
+            for (axis, &(code, _)) in axes.iter_mut().zip(AXES.iter()) {
+                let mut info: libc::input_absinfo = mem::zeroed();
+                if libc::ioctl(fd, eviocgabs(code) as _, &mut info) == 0
+                    && info.maximum > info.minimum
+                {
+                    *axis = Some(info);
+                }
+            }
+
+            let device = Device {
+                file,
+                path: path.to_owned(),
+                axes,
+                dropped: false,
+            };
+
+            Some((device, name))
+        }
+    }
+
+    /// Converts the value of an axis to the range documented in `GamepadAxis`
+    fn axis_value(&self, axis: GamepadAxis, value: i32) -> f32 {
+        let info = match self.axes[axis as usize] {
+            Some(info) => info,
+            None => return 0.0,
+        };
+        let range = (info.maximum - info.minimum) as f32;
+        let value = value.clamp(info.minimum, info.maximum);
+
+        match axis {
+            GamepadAxis::LeftTrigger | GamepadAxis::RightTrigger => {
+                (value - info.minimum) as f32 / range
+            }
+            _ => {
+                // the kernel reports the size of the dead zone (if any) as flat
+                let center = info.minimum + (info.maximum - info.minimum) / 2;
+                if (value - center).abs() <= info.flat {
+                    0.0
+                } else {
+                    (value - info.minimum) as f32 / range * 2.0 - 1.0
+                }
+            }
+        }
+    }
+
+    /// Reads the current state of the device, used when it's opened and after events were dropped
+    fn read_state(&self, gamepad: &mut Gamepad, events: &mut EventBuffer) {
+        let fd = self.file.as_raw_fd();
+        let mut keys = [0u8; KEY_MAX / 8 + 1];
+        let mut hats = [0; 2];
+
+        unsafe {
+            libc::ioctl(fd, eviocgkey(keys.len()) as _, keys.as_mut_ptr());
+
+            for (hat, code) in hats.iter_mut().zip([ABS_HAT0X, ABS_HAT0Y]) {
+                let mut info: libc::input_absinfo = mem::zeroed();
+                if libc::ioctl(fd, eviocgabs(code) as _, &mut info) == 0 {
+                    *hat = info.value;
+                }
+            }
+        }
+
+        for &(code, button) in BUTTONS.iter() {
+            let pressed = test_bit(&keys, code as usize);
+            set_button(gamepad, button, pressed, events);
+        }
+
+        for &(code, axis) in AXES.iter() {
+            if self.axes[axis as usize].is_some() {
+                let mut info: libc::input_absinfo = unsafe { mem::zeroed() };
+                unsafe { libc::ioctl(fd, eviocgabs(code) as _, &mut info) };
+                let value = self.axis_value(axis, info.value);
+                set_axis(gamepad, axis, value, events);
+            }
+        }
+
+        set_hat(gamepad, ABS_HAT0X, hats[0], events);
+        set_hat(gamepad, ABS_HAT0Y, hats[1], events);
+    }
+
+    /// Reads the pending events, returns false if the device is gone
+    fn read_events(&mut self, gamepad: &mut Gamepad, events: &mut EventBuffer) -> bool {
+        let mut input_events: [libc::input_event; 64] = unsafe { mem::zeroed() };
+
+        loop {
+            let size = unsafe {
+                libc::read(
+                    self.file.as_raw_fd(),
+                    input_events.as_mut_ptr() as *mut libc::c_void,
+                    mem::size_of_val(&input_events),
+                )
+            };
+
+            if size < 0 {
+                let error = std::io::Error::last_os_error();
+                return error.kind() == ErrorKind::WouldBlock
+                    || error.kind() == ErrorKind::Interrupted;
+            }
+
+            let count = size as usize / mem::size_of::<libc::input_event>();
+            for input_event in &input_events[..count] {
+                self.process_event(input_event, gamepad, events);
+            }
+
+            if count < input_events.len() {
+                return true;
+            }
+        }
+    }
+
+    fn process_event(
+        &mut self,
+        input_event: &libc::input_event,
+        gamepad: &mut Gamepad,
+        events: &mut EventBuffer,
+    ) {
+        if input_event.type_ == EV_SYN {
+            match input_event.code {
+                SYN_DROPPED => self.dropped = true,
+                SYN_REPORT if self.dropped => {
+                    self.dropped = false;
+                    self.read_state(gamepad, events);
+                }
+                _ => (),
+            }
+            return;
+        }
+
+        if self.dropped {
+            return;
+        }
+
+        let time = input_event.time.tv_sec as u64 * 1000 + input_event.time.tv_usec as u64 / 1000;
+        events.set_time_ms(time as u32);
+
+        let code = input_event.code;
+        let value = input_event.value;
+
+        match input_event.type_ {
+            EV_KEY => {
+                if let Some(&(_, button)) = BUTTONS.iter().find(|&&(c, _)| c == code) {
+                    // 2 is auto repeat
+                    set_button(gamepad, button, value != 0, events);
+                }
+            }
+            EV_ABS => {
+                if let Some(&(_, axis)) = AXES.iter().find(|&&(c, _)| c == code) {
+                    let value = self.axis_value(axis, value);
+                    set_axis(gamepad, axis, value, events);
+                } else {
+                    set_hat(gamepad, code, value, events);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+fn set_button(
+    gamepad: &mut Gamepad,
+    button: GamepadButton,
+    pressed: bool,
+    events: &mut EventBuffer,
+) {
+    if gamepad.buttons[button as usize] != pressed {
+        gamepad.buttons[button as usize] = pressed;
+        events.push(Event::GamepadButton {
+            id: gamepad.id,
+            button,
+            pressed,
+        });
+    }
+}
+
+fn set_axis(gamepad: &mut Gamepad, axis: GamepadAxis, value: f32, events: &mut EventBuffer) {
+    if gamepad.axes[axis as usize] != value {
+        gamepad.axes[axis as usize] = value;
+        events.push(Event::GamepadAxis {
+            id: gamepad.id,
+            axis,
+            value,
+        });
+    }
+}
+
+/// Most gamepads reports the d-pad as a hat axis, turn it into buttons
+fn set_hat(gamepad: &mut Gamepad, code: u16, value: i32, events: &mut EventBuffer) {
+    let (negative, positive) = match code {
+        ABS_HAT0X => (GamepadButton::DPadLeft, GamepadButton::DPadRight),
+        ABS_HAT0Y => (GamepadButton::DPadUp, GamepadButton::DPadDown),
+        _ => return,
+    };
+
+    set_button(gamepad, negative, value < 0, events);
+    set_button(gamepad, positive, value > 0, events);
+}
+
+/// A change of an event device in the input directory, as reported by inotify
+#[derive(Debug, PartialEq)]
+enum DeviceChange {
+    Added(PathBuf),
+    Removed(PathBuf),
+}
+
+/// The gamepads connected to the computer, updated by the window
+pub struct Gamepads {
+    dir: PathBuf,
+    inotify: c_int,
+    scanned: bool,
+    next_id: u32,
+    devices: Vec<Device>,
+    gamepads: Vec<Gamepad>,
+}
+
+impl Gamepads {
+    #[inline]
+    pub fn new() -> Gamepads {
+        Gamepads::with_dir(INPUT_DIR)
+    }
+
+    /// Reads the gamepads from the event devices in `dir` instead of `/dev/input`
+    pub fn with_dir<P: Into<PathBuf>>(dir: P) -> Gamepads {
+        let dir = dir.into();
+        let inotify = unsafe {
+            let fd = libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC);
+            let path = CString::new(dir.as_os_str().as_bytes()).unwrap_or_default();
+            // udev sets the permissions after the device is created, hence IN_ATTRIB
+            let mask = libc::IN_CREATE | libc::IN_ATTRIB | libc::IN_DELETE;
+            if fd >= 0 && libc::inotify_add_watch(fd, path.as_ptr(), mask) < 0 {
+                libc::close(fd);
+                -1
+            } else {
+                fd
+            }
+        };
+
+        Gamepads {
+            dir,
+            inotify,
+            scanned: false,
+            next_id: 0,
+            devices: Vec::new(),
+            gamepads: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn get_gamepads(&self) -> &[Gamepad] {
+        &self.gamepads
+    }
+
+    /// Reads the gamepad events and pushes them to `events`. The gamepads that are connected
+    /// when the window is created are reported on the first update.
+    pub fn update(&mut self, events: &mut EventBuffer) {
+        if !self.scanned {
+            self.scanned = true;
+            self.scan(events);
+        }
+
+        self.process_inotify_events(events);
+
+        let mut index = 0;
+        while index < self.devices.len() {
+            let gamepad = &mut self.gamepads[index];
+            if self.devices[index].read_events(gamepad, events) {
+                index += 1;
+            } else {
+                self.remove(index, events);
+            }
+        }
+    }
+
+    fn scan(&mut self, events: &mut EventBuffer) {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| is_event_device(path))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            self.add(&path, events);
+        }
+    }
+
+    fn process_inotify_events(&mut self, events: &mut EventBuffer) {
+        for change in self.read_device_changes() {
+            match change {
+                DeviceChange::Added(path) => self.add(&path, events),
+                DeviceChange::Removed(path) => {
+                    if let Some(index) = self.devices.iter().position(|d| d.path == path) {
+                        self.remove(index, events);
+                    }
+                }
+            }
+        }
+    }
+
+    fn read_device_changes(&self) -> Vec<DeviceChange> {
+        let mut changes = Vec::new();
+        if self.inotify < 0 {
+            return changes;
+        }
+
+        // aligned for reading the events
+        let mut buffer = [0u32; 1024];
+
+        loop {
+            let size = unsafe {
+                libc::read(
+                    self.inotify,
+                    buffer.as_mut_ptr() as *mut libc::c_void,
+                    mem::size_of_val(&buffer),
+                )
+            };
+            if size <= 0 {
+                return changes;
+            }
+
+            let bytes =
+                unsafe { std::slice::from_raw_parts(buffer.as_ptr() as *const u8, size as usize) };
+            let header_size = mem::size_of::<libc::inotify_event>();
+            let mut offset = 0;
+
+            while offset + header_size <= bytes.len() {
+                let event = unsafe {
+                    std::ptr::read_unaligned(bytes[offset..].as_ptr() as *const libc::inotify_event)
+                };
+                let name_end = (offset + header_size + event.len as usize).min(bytes.len());
+                let name = &bytes[offset + header_size..name_end];
+                offset = name_end;
+
+                let name = match CStr::from_bytes_until_nul(name) {
+                    Ok(name) => name.to_string_lossy().into_owned(),
+                    Err(_) => continue,
+                };
+                let path = self.dir.join(name);
+                if !is_event_device(&path) {
+                    continue;
+                }
+
+                if event.mask & libc::IN_DELETE != 0 {
+                    changes.push(DeviceChange::Removed(path));
+                } else {
+                    changes.push(DeviceChange::Added(path));
+                }
+            }
+        }
+    }
+
+    fn add(&mut self, path: &Path, events: &mut EventBuffer) {
+        if self.devices.iter().any(|device| device.path == path) {
+            return;
+        }
+
+        if let Some((device, name)) = Device::open(path) {
+            let mut gamepad = Gamepad::new(self.next_id, name);
+            self.next_id += 1;
+
+            events.push(Event::GamepadConnected(gamepad.id));
+            device.read_state(&mut gamepad, events);
+
+            self.devices.push(device);
+            self.gamepads.push(gamepad);
+        }
+    }
+
+    fn remove(&mut self, index: usize, events: &mut EventBuffer) {
+        self.devices.remove(index);
+        let gamepad = self.gamepads.remove(index);
+        events.push(Event::GamepadDisconnected(gamepad.id));
+    }
+}
+
+impl Drop for Gamepads {
+    fn drop(&mut self) {
+        if self.inotify >= 0 {
+            unsafe { libc::close(self.inotify) };
+        }
+    }
+}
+
+#[inline]
+fn is_event_device(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with("event"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, os::unix::fs::PermissionsExt, thread, time::Duration};
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("minifb-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir(&dir).unwrap();
+        dir
+    }
+
+    fn absinfo(minimum: i32, maximum: i32, flat: i32) -> Option<libc::input_absinfo> {
+        Some(libc::input_absinfo {
+            value: 0,
+            minimum,
+            maximum,
+            fuzz: 0,
+            flat,
+            resolution: 0,
+        })
+    }
+
+    // a device that isn't an evdev device, reading its state just fails and gives released
+    // buttons and centered axes
+    fn fake_device(path: PathBuf) -> Device {
+        let mut axes = [None; GAMEPAD_AXIS_COUNT];
+        axes[GamepadAxis::LeftStickX as usize] = absinfo(-32768, 32767, 128);
+        axes[GamepadAxis::LeftTrigger as usize] = absinfo(0, 255, 0);
+
+        Device {
+            file: File::open("/dev/null").unwrap(),
+            path,
+            axes,
+            dropped: false,
+        }
+    }
+
+    fn input_event(type_: u16, code: u16, value: i32) -> libc::input_event {
+        libc::input_event {
+            time: libc::timeval {
+                tv_sec: 0,
+                tv_usec: 0,
+            },
+            type_,
+            code,
+            value,
+        }
+    }
+
+    fn pushed_events(events: &EventBuffer) -> Vec<Event> {
+        events.iter().map(|event| event.event.clone()).collect()
+    }
+
+    #[test]
+    fn event_devices() {
+        assert!(is_event_device(Path::new("/dev/input/event0")));
+        assert!(is_event_device(Path::new("/dev/input/event12")));
+        assert!(!is_event_device(Path::new("/dev/input/js0")));
+        assert!(!is_event_device(Path::new("/dev/input/mouse0")));
+        assert!(!is_event_device(Path::new("/dev/input/by-id")));
+    }
+
+    #[test]
+    fn device_changes() {
+        let dir = test_dir("gamepad-changes");
+        let gamepads = Gamepads::with_dir(&dir);
+        let event = dir.join("event0");
+
+        fs::write(&event, b"").unwrap();
+        fs::write(dir.join("js0"), b"").unwrap();
+        fs::set_permissions(&event, fs::Permissions::from_mode(0o600)).unwrap();
+        fs::remove_file(&event).unwrap();
+
+        assert_eq!(
+            gamepads.read_device_changes(),
+            [
+                DeviceChange::Added(event.clone()),
+                DeviceChange::Added(event.clone()),
+                DeviceChange::Removed(event),
+            ]
+        );
+        assert_eq!(gamepads.read_device_changes(), []);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn removed_device() {
+        let dir = test_dir("gamepad-removed");
+        let mut gamepads = Gamepads::with_dir(&dir);
+        let mut events = EventBuffer::new();
+        let event = dir.join("event0");
+        fs::write(&event, b"").unwrap();
+
+        // the file isn't an evdev device so it's not added as a gamepad
+        gamepads.update(&mut events);
+        assert!(gamepads.get_gamepads().is_empty());
+        assert_eq!(pushed_events(&events), []);
+
+        gamepads.devices.push(fake_device(event.clone()));
+        gamepads.gamepads.push(Gamepad::new(7, String::new()));
+
+        fs::remove_file(&event).unwrap();
+        gamepads.update(&mut events);
+        assert!(gamepads.get_gamepads().is_empty());
+        assert_eq!(pushed_events(&events), [Event::GamepadDisconnected(7)]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn stick_values() {
+        let device = fake_device(PathBuf::new());
+        let axis = GamepadAxis::LeftStickX;
+
+        assert_eq!(device.axis_value(axis, -32768), -1.0);
+        assert_eq!(device.axis_value(axis, 32767), 1.0);
+        assert_eq!(device.axis_value(axis, -40000), -1.0);
+        assert_eq!(device.axis_value(axis, 0), 0.0);
+        // inside the dead zone
+        assert_eq!(device.axis_value(axis, 127), 0.0);
+        assert_eq!(device.axis_value(axis, -129), 0.0);
+        // outside of it
+        assert!(device.axis_value(axis, 200) > 0.0);
+        assert!(device.axis_value(axis, -200) < 0.0);
+        // axes the device doesn't have
+        assert_eq!(device.axis_value(GamepadAxis::RightStickX, 1000), 0.0);
+    }
+
+    #[test]
+    fn trigger_values() {
+        let device = fake_device(PathBuf::new());
+        let axis = GamepadAxis::LeftTrigger;
+
+        assert_eq!(device.axis_value(axis, 0), 0.0);
+        assert_eq!(device.axis_value(axis, 255), 1.0);
+        assert_eq!(device.axis_value(axis, 300), 1.0);
+        assert_eq!(device.axis_value(axis, 51), 0.2);
+        assert_eq!(device.axis_value(GamepadAxis::RightTrigger, 255), 0.0);
+    }
+
+    #[test]
+    fn hat_to_dpad() {
+        let mut gamepad = Gamepad::new(0, String::new());
+        let mut events = EventBuffer::new();
+
+        set_hat(&mut gamepad, ABS_HAT0X, -1, &mut events);
+        set_hat(&mut gamepad, ABS_HAT0Y, 1, &mut events);
+        assert!(gamepad.is_button_down(GamepadButton::DPadLeft));
+        assert!(!gamepad.is_button_down(GamepadButton::DPadRight));
+        assert!(!gamepad.is_button_down(GamepadButton::DPadUp));
+        assert!(gamepad.is_button_down(GamepadButton::DPadDown));
+
+        set_hat(&mut gamepad, ABS_HAT0X, 1, &mut events);
+        set_hat(&mut gamepad, ABS_HAT0Y, 0, &mut events);
+        assert!(!gamepad.is_button_down(GamepadButton::DPadLeft));
+        assert!(gamepad.is_button_down(GamepadButton::DPadRight));
+        assert!(!gamepad.is_button_down(GamepadButton::DPadDown));
+
+        // other axes are ignored
+        set_hat(&mut gamepad, 0x12, 1, &mut events);
+
+        let button = |button, pressed| Event::GamepadButton {
+            id: 0,
+            button,
+            pressed,
+        };
+        assert_eq!(
+            pushed_events(&events),
+            [
+                button(GamepadButton::DPadLeft, true),
+                button(GamepadButton::DPadDown, true),
+                button(GamepadButton::DPadLeft, false),
+                button(GamepadButton::DPadRight, true),
+                button(GamepadButton::DPadDown, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn resync_after_dropped_events() {
+        let mut device = fake_device(PathBuf::new());
+        let mut gamepad = Gamepad::new(0, String::new());
+        let mut events = EventBuffer::new();
+        let south = BUTTONS[0].0;
+        let east = BUTTONS[1].0;
+
+        device.process_event(&input_event(EV_KEY, south, 1), &mut gamepad, &mut events);
+        device.process_event(
+            &input_event(EV_SYN, SYN_REPORT, 0),
+            &mut gamepad,
+            &mut events,
+        );
+        assert!(gamepad.is_button_down(GamepadButton::South));
+
+        // the events until the next report are skipped
+        device.process_event(
+            &input_event(EV_SYN, SYN_DROPPED, 0),
+            &mut gamepad,
+            &mut events,
+        );
+        device.process_event(&input_event(EV_KEY, east, 1), &mut gamepad, &mut events);
+        assert!(!gamepad.is_button_down(GamepadButton::East));
+
+        // and the state is read from the device (where nothing is pressed) on the report
+        device.process_event(
+            &input_event(EV_SYN, SYN_REPORT, 0),
+            &mut gamepad,
+            &mut events,
+        );
+        assert!(!gamepad.is_button_down(GamepadButton::South));
+        assert!(!gamepad.is_button_down(GamepadButton::East));
+
+        device.process_event(&input_event(EV_KEY, east, 1), &mut gamepad, &mut events);
+        assert!(gamepad.is_button_down(GamepadButton::East));
+    }
+
+    // needs write access to /dev/uinput and read access to the created device
+    #[test]
+    #[ignore]
+    fn uinput_gamepad() {
+        const UI_SET_EVBIT: c_ulong = 0x4004_5564;
+        const UI_SET_KEYBIT: c_ulong = 0x4004_5565;
+        const UI_SET_ABSBIT: c_ulong = 0x4004_5567;
+        const UI_DEV_CREATE: c_ulong = 0x5501;
+        const UI_DEV_DESTROY: c_ulong = 0x5502;
+        const NAME: &[u8] = b"minifb test gamepad";
+
+        let uinput = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open("/dev/uinput")
+            .unwrap();
+        let fd = uinput.as_raw_fd();
+
+        let write = |value: &[u8]| {
+            let size =
+                unsafe { libc::write(fd, value.as_ptr() as *const libc::c_void, value.len()) };
+            assert_eq!(size as usize, value.len());
+        };
+        let write_event = |type_, code, value| {
+            let event = input_event(type_, code, value);
+            write(unsafe {
+                std::slice::from_raw_parts(
+                    &event as *const _ as *const u8,
+                    mem::size_of::<libc::input_event>(),
+                )
+            });
+        };
+
+        unsafe {
+            libc::ioctl(fd, UI_SET_EVBIT as _, EV_KEY as c_int);
+            for &(code, _) in BUTTONS.iter() {
+                libc::ioctl(fd, UI_SET_KEYBIT as _, code as c_int);
+            }
+            libc::ioctl(fd, UI_SET_EVBIT as _, EV_ABS as c_int);
+            libc::ioctl(fd, UI_SET_ABSBIT as _, AXES[0].0 as c_int);
+
+            let mut setup: libc::uinput_user_dev = mem::zeroed();
+            for (c, &b) in setup.name.iter_mut().zip(NAME) {
+                *c = b as libc::c_char;
+            }
+            setup.id.bustype = 0x06; // BUS_VIRTUAL
+            setup.absmin[AXES[0].0 as usize] = -100;
+            setup.absmax[AXES[0].0 as usize] = 100;
+            write(std::slice::from_raw_parts(
+                &setup as *const _ as *const u8,
+                mem::size_of::<libc::uinput_user_dev>(),
+            ));
+            assert_eq!(libc::ioctl(fd, UI_DEV_CREATE as _), 0);
+        }
+
+        let mut gamepads = Gamepads::new();
+        let mut events = EventBuffer::new();
+
+        // wait for udev to set the permissions of the device
+        let mut id = None;
+        for _ in 0..100 {
+            gamepads.update(&mut events);
+            id = gamepads
+                .get_gamepads()
+                .iter()
+                .find(|gamepad| gamepad.name().as_bytes() == NAME)
+                .map(|gamepad| gamepad.id());
+            if id.is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        let id = id.expect("the uinput gamepad wasn't found");
+
+        events.clear();
+        write_event(EV_KEY, BUTTONS[0].0, 1);
+        write_event(EV_ABS, AXES[0].0, 100);
+        write_event(EV_SYN, SYN_REPORT, 0);
+        thread::sleep(Duration::from_millis(50));
+        gamepads.update(&mut events);
+
+        assert!(pushed_events(&events).contains(&Event::GamepadButton {
+            id,
+            button: GamepadButton::South,
+            pressed: true,
+        }));
+        assert!(pushed_events(&events).contains(&Event::GamepadAxis {
+            id,
+            axis: GamepadAxis::LeftStickX,
+            value: 1.0,
+        }));
+
+        unsafe { libc::ioctl(fd, UI_DEV_DESTROY as _) };
+        thread::sleep(Duration::from_millis(50));
+        events.clear();
+        gamepads.update(&mut events);
+        assert!(pushed_events(&events).contains(&Event::GamepadDisconnected(id)));
+    }
+}
@@ -10,6 +10,9 @@
 
 mod common;
 
+#[cfg(all(feature = "gamepad", target_os = "linux"))]
+mod gamepad;
+
 #[cfg(feature = "wayland")]
 mod wayland;
 #[cfg(feature = "x11")]
@@ -20,10 +23,12 @@ mod xkb_ffi;
 mod xkb_keysyms;
 
 use crate::{
-    icon::Icon, CursorStyle, InputCallback, Key, KeyRepeat, MenuHandle, MouseButton, MouseMode,
-    Result, UnixMenu, WindowOptions,
+    icon::Icon, CursorGrab, CursorStyle, CustomCursor, Fullscreen, InputCallback, Key, KeyRepeat,
+    MenuHandle, Modifiers, Monitor, MouseButton, MouseMode, PenState, Result, Scancode, TimedEvent,
+    Touch, UnixMenu, WindowOptions, WindowSize,
 };
 pub use common::Menu;
+use common::{ClipboardData, Selection, TEXT_MIME_TYPES};
 use raw_window_handle::{
     DisplayHandle, HandleError, HasDisplayHandle, HasWindowHandle, WindowHandle,
 };
@@ -158,8 +163,13 @@ impl Window {
         }
     }
 
-    pub fn topmost(&self, _topmost: bool) {
-        // We will just do nothing until it is implemented so that nothing breaks
+    pub fn topmost(&self, topmost: bool) -> Result<()> {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.topmost(topmost),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.topmost(topmost),
+        }
     }
 
     pub fn get_size(&self) -> (usize, usize) {
@@ -207,6 +217,187 @@ impl Window {
         }
     }
 
+    pub fn set_cursor_grab(&mut self, grab: CursorGrab) -> Result<()> {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.set_cursor_grab(grab),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.set_cursor_grab(grab),
+        }
+    }
+
+    pub fn get_mouse_delta(&self) -> (f32, f32) {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.get_mouse_delta(),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.get_mouse_delta(),
+        }
+    }
+
+    pub fn set_mouse_pos(&mut self, x: f32, y: f32) -> Result<()> {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.set_mouse_pos(x, y),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.set_mouse_pos(x, y),
+        }
+    }
+
+    pub fn get_touches(&self) -> &[Touch] {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.get_touches(),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.get_touches(),
+        }
+    }
+
+    pub fn get_pen_state(&self) -> Option<PenState> {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.get_pen_state(),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.get_pen_state(),
+        }
+    }
+
+    #[cfg(all(feature = "gamepad", target_os = "linux"))]
+    pub fn get_gamepads(&self) -> &[crate::Gamepad] {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.get_gamepads(),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.get_gamepads(),
+        }
+    }
+
+    pub fn get_click_count(&self, button: MouseButton) -> u32 {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.get_click_count(button),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.get_click_count(button),
+        }
+    }
+
+    pub fn set_default_double_click_interval(&mut self, interval: Duration) {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.set_default_double_click_interval(interval),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.set_default_double_click_interval(interval),
+        }
+    }
+
+    pub fn set_fullscreen(&mut self, fullscreen: Fullscreen) -> Result<()> {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.set_fullscreen(fullscreen),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.set_fullscreen(fullscreen),
+        }
+    }
+
+    pub fn monitors(&self) -> Vec<Monitor> {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.monitors(),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.monitors(),
+        }
+    }
+
+    pub fn current_monitor(&self) -> Option<Monitor> {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.current_monitor(),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.current_monitor(),
+        }
+    }
+
+    pub fn set_size(&mut self, width: usize, height: usize) {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.set_size(width, height),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.set_size(width, height),
+        }
+    }
+
+    pub fn set_min_size(&mut self, size: Option<(usize, usize)>) {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.set_min_size(size),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.set_min_size(size),
+        }
+    }
+
+    pub fn set_max_size(&mut self, size: Option<(usize, usize)>) {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.set_max_size(size),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.set_max_size(size),
+        }
+    }
+
+    pub fn set_aspect_ratio(&mut self, ratio: Option<(usize, usize)>) {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.set_aspect_ratio(ratio),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.set_aspect_ratio(ratio),
+        }
+    }
+
+    pub fn set_maximized(&mut self, maximized: bool) {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.set_maximized(maximized),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.set_maximized(maximized),
+        }
+    }
+
+    pub fn set_minimized(&mut self, minimized: bool) {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.set_minimized(minimized),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.set_minimized(minimized),
+        }
+    }
+
+    pub fn is_maximized(&self) -> bool {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.is_maximized(),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.is_maximized(),
+        }
+    }
+
+    pub fn is_minimized(&self) -> bool {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.is_minimized(),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.is_minimized(),
+        }
+    }
+
+    pub fn set_custom_cursor(&mut self, cursor: &CustomCursor) {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.set_custom_cursor(cursor),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.set_custom_cursor(cursor),
+        }
+    }
+
     pub fn set_cursor_style(&mut self, cursor: CursorStyle) {
         match self {
             #[cfg(feature = "x11")]
@@ -377,6 +568,160 @@ impl Window {
             Window::Wayland(w) => w.is_menu_pressed(),
         }
     }
+
+    pub fn poll_events(&self) -> std::slice::Iter<'_, TimedEvent> {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.poll_events(),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.poll_events(),
+        }
+    }
+
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, TimedEvent> {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.drain_events(),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.drain_events(),
+        }
+    }
+
+    pub fn set_resize_callback(&mut self, callback: Box<dyn FnMut(WindowSize)>) {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.set_resize_callback(callback),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.set_resize_callback(callback),
+        }
+    }
+
+    pub fn close_requested(&self) -> bool {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.close_requested(),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.close_requested(),
+        }
+    }
+
+    pub fn cancel_close(&mut self) {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.cancel_close(),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.cancel_close(),
+        }
+    }
+
+    pub fn close(&mut self) {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.close(),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.close(),
+        }
+    }
+
+    pub fn get_modifiers(&self) -> Modifiers {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.get_modifiers(),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.get_modifiers(),
+        }
+    }
+
+    pub fn set_ime_position(&mut self, x: usize, y: usize) {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.set_ime_position(x, y),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.set_ime_position(x, y),
+        }
+    }
+
+    pub fn set_clipboard_text(&mut self, text: &str) {
+        self.set_selection_text(Selection::Clipboard, text);
+    }
+
+    pub fn get_clipboard_text(&mut self) -> Option<String> {
+        self.get_selection_text(Selection::Clipboard)
+    }
+
+    pub fn set_primary_selection(&mut self, text: &str) {
+        self.set_selection_text(Selection::Primary, text);
+    }
+
+    pub fn get_primary_selection(&mut self) -> Option<String> {
+        self.get_selection_text(Selection::Primary)
+    }
+
+    fn set_selection_text(&mut self, selection: Selection, text: &str) {
+        let data = TEXT_MIME_TYPES
+            .iter()
+            .map(|mime_type| (*mime_type, text.as_bytes().to_vec()))
+            .collect();
+
+        self.set_selection(selection, data);
+    }
+
+    fn get_selection_text(&mut self, selection: Selection) -> Option<String> {
+        let text = self.get_selection(selection, &TEXT_MIME_TYPES)?;
+        Some(String::from_utf8_lossy(&text).into_owned())
+    }
+
+    #[cfg(feature = "clipboard-image")]
+    pub fn set_clipboard_image(
+        &mut self,
+        buffer: &[u32],
+        width: usize,
+        height: usize,
+    ) -> Result<()> {
+        crate::check_buffer_size(buffer, width, height, width)?;
+
+        let png_data = common::encode_png(buffer, width, height)
+            .ok_or_else(|| crate::Error::UpdateFailed("Unable to encode the image".to_owned()))?;
+        self.set_selection(
+            Selection::Clipboard,
+            vec![(common::IMAGE_MIME_TYPE, png_data)],
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "clipboard-image")]
+    pub fn get_clipboard_image(&mut self) -> Option<crate::ClipboardImage> {
+        let png_data = self.get_selection(Selection::Clipboard, &[common::IMAGE_MIME_TYPE])?;
+        common::decode_png(&png_data)
+    }
+
+    fn set_selection(&mut self, selection: Selection, data: ClipboardData) {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.set_selection(selection, data),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.set_selection(selection, data),
+        }
+    }
+
+    fn get_selection(&mut self, selection: Selection, mime_types: &[&str]) -> Option<Vec<u8>> {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.get_selection(selection, mime_types),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.get_selection(selection, mime_types),
+        }
+    }
+
+    pub fn is_scancode_down(&self, scancode: Scancode) -> bool {
+        match self {
+            #[cfg(feature = "x11")]
+            Window::X11(w) => w.is_scancode_down(scancode),
+            #[cfg(feature = "wayland")]
+            Window::Wayland(w) => w.is_scancode_down(scancode),
+        }
+    }
 }
 
 impl HasWindowHandle for Window {
@@ -1,9 +1,10 @@
 use std::{
     cell::RefCell,
+    convert::{TryFrom, TryInto},
     ffi::c_void,
     fs::File,
-    io::{Seek, SeekFrom, Write},
-    os::unix::io::{AsRawFd, RawFd},
+    io::{Read, Seek, SeekFrom, Write},
+    os::unix::io::{AsRawFd, FromRawFd, RawFd},
     ptr::NonNull,
     rc::Rc,
     sync::mpsc,
@@ -11,12 +12,17 @@ use std::{
 };
 
 use super::common::{
-    image_center, image_resize_linear, image_resize_linear_aspect_fill, image_upper_left, Menu,
+    image_center, image_resize_linear, image_resize_linear_aspect_fill, image_upper_left,
+    parse_uri_list, premultiply_alpha, ClickCounter, ClipboardData, Menu, Selection, CURSOR_NAMES,
+    PIXELS_PER_NOTCH, URI_LIST_MIME_TYPE,
 };
+#[cfg(all(feature = "gamepad", target_os = "linux"))]
+use super::gamepad::Gamepads;
 use crate::{
-    check_buffer_size, key_handler::KeyHandler, rate::UpdateRate, CursorStyle, Error,
-    InputCallback, Key, KeyRepeat, MenuHandle, MouseButton, MouseMode, Result, Scale, ScaleMode,
-    UnixMenu, WindowOptions,
+    check_buffer_size, event::EventBuffer, key_handler::KeyHandler, rate::UpdateRate, CursorGrab,
+    CursorStyle, CustomCursor, Error, Event, Fullscreen, InputCallback, Key, KeyRepeat, MenuHandle,
+    Modifiers, Monitor, MouseButton, MouseMode, PenState, Result, Scale, ScaleMode, Scancode,
+    ScrollSource, TimedEvent, Touch, TouchPhase, UnixMenu, WindowOptions, WindowSize,
 };
 use raw_window_handle::{
     DisplayHandle, HandleError, HasDisplayHandle, HasWindowHandle, RawDisplayHandle,
@@ -26,18 +32,54 @@ use wayland_client::{
     protocol::{
         wl_buffer::WlBuffer,
         wl_compositor::WlCompositor,
+        wl_data_device::{self, WlDataDevice},
+        wl_data_device_manager::{DndAction, WlDataDeviceManager},
+        wl_data_offer::{self, WlDataOffer},
+        wl_data_source::{self, WlDataSource},
         wl_display::WlDisplay,
         wl_keyboard::{self, KeymapFormat, WlKeyboard},
+        wl_output::{self, WlOutput},
         wl_pointer::{self, WlPointer},
-        wl_seat::WlSeat,
+        wl_seat::{self, WlSeat},
         wl_shm::{Format, WlShm},
         wl_shm_pool::WlShmPool,
-        wl_surface::WlSurface,
+        wl_surface::{self, WlSurface},
+        wl_touch::{self, WlTouch},
     },
-    Attached, Display, EventQueue, GlobalManager, Main,
+    Attached, Display, EventQueue, GlobalEvent, GlobalManager, Interface, Main, Proxy,
 };
 use wayland_protocols::{
-    unstable::xdg_decoration::v1::client::zxdg_decoration_manager_v1::ZxdgDecorationManagerV1,
+    unstable::{
+        pointer_constraints::v1::client::{
+            zwp_confined_pointer_v1::ZwpConfinedPointerV1,
+            zwp_locked_pointer_v1::ZwpLockedPointerV1,
+            zwp_pointer_constraints_v1::{Lifetime, ZwpPointerConstraintsV1},
+        },
+        primary_selection::v1::client::{
+            zwp_primary_selection_device_manager_v1::ZwpPrimarySelectionDeviceManagerV1,
+            zwp_primary_selection_device_v1::{self, ZwpPrimarySelectionDeviceV1},
+            zwp_primary_selection_offer_v1::{self, ZwpPrimarySelectionOfferV1},
+            zwp_primary_selection_source_v1::{self, ZwpPrimarySelectionSourceV1},
+        },
+        relative_pointer::v1::client::{
+            zwp_relative_pointer_manager_v1::ZwpRelativePointerManagerV1,
+            zwp_relative_pointer_v1::{self, ZwpRelativePointerV1},
+        },
+        tablet::v2::client::{
+            zwp_tablet_manager_v2::ZwpTabletManagerV2,
+            zwp_tablet_seat_v2::{self, ZwpTabletSeatV2},
+            zwp_tablet_tool_v2,
+        },
+        text_input::v3::client::{
+            zwp_text_input_manager_v3::ZwpTextInputManagerV3,
+            zwp_text_input_v3::{self, ZwpTextInputV3},
+        },
+        xdg_decoration::v1::client::zxdg_decoration_manager_v1::ZxdgDecorationManagerV1,
+        xdg_output::v1::client::{
+            zxdg_output_manager_v1::ZxdgOutputManagerV1,
+            zxdg_output_v1::{self, ZxdgOutputV1},
+        },
+    },
     xdg_shell::client::{
         xdg_surface::XdgSurface, xdg_toplevel::XdgToplevel, xdg_wm_base::XdgWmBase,
     },
@@ -50,14 +92,19 @@ use super::xkb_ffi::XKBCOMMON_HANDLE as XKBH;
 use super::xkb_ffi::*;
 
 const KEY_XKB_OFFSET: u32 = 8;
+// How long to wait for other clients when reading the clipboard
+const SELECTION_TIMEOUT_MS: i32 = 1000;
 const KEY_MOUSE_BTN1: u32 = 272;
 const KEY_MOUSE_BTN2: u32 = 273;
 const KEY_MOUSE_BTN3: u32 = 274;
 const KEY_MOUSE_BTN8: u32 = 275;
 const KEY_MOUSE_BTN9: u32 = 276;
+const KEY_STYLUS_BTN1: u32 = 331;
+const KEY_STYLUS_BTN2: u32 = 332;
 
 type ToplevelResolution = Rc<RefCell<Option<(i32, i32)>>>;
 type ToplevelClosed = Rc<RefCell<bool>>;
+type ToplevelMaximized = Rc<RefCell<bool>>;
 
 struct Buffer {
     fd: File,
@@ -157,15 +204,126 @@ impl BufferPool {
     }
 }
 
+/// What the compositor told about an output
+#[derive(Default)]
+struct OutputInfo {
+    name: String,
+    position: (i32, i32),
+    // the position from xdg-output is in the compositor space, unlike the one from wl_output
+    logical_position: bool,
+    size: (i32, i32),
+    refresh_rate: i32,
+    scale_factor: i32,
+}
+
+struct Output {
+    global_id: u32,
+    output: Main<WlOutput>,
+    xdg_output: Option<Main<ZxdgOutputV1>>,
+    info: Rc<RefCell<OutputInfo>>,
+}
+
+type Outputs = Rc<RefCell<Vec<Output>>>;
+
+impl Output {
+    fn new(global_id: u32, output: Main<WlOutput>) -> Self {
+        let info = Rc::new(RefCell::new(OutputInfo {
+            scale_factor: 1,
+            ..OutputInfo::default()
+        }));
+        let info_clone = info.clone();
+
+        output.quick_assign(move |_, event, _| {
+            let mut info = info_clone.borrow_mut();
+            match event {
+                wl_output::Event::Geometry { x, y, model, .. } => {
+                    if !info.logical_position {
+                        info.position = (x, y);
+                    }
+                    // the connector name needs version 4 or xdg-output, the model is better than nothing
+                    if info.name.is_empty() {
+                        info.name = model;
+                    }
+                }
+                wl_output::Event::Mode {
+                    flags,
+                    width,
+                    height,
+                    refresh,
+                } if flags.contains(wl_output::Mode::Current) => {
+                    info.size = (width, height);
+                    info.refresh_rate = refresh;
+                }
+                wl_output::Event::Scale { factor } => info.scale_factor = factor,
+                wl_output::Event::Name { name } => info.name = name,
+                _ => (),
+            }
+        });
+
+        Self {
+            global_id,
+            output,
+            xdg_output: None,
+            info,
+        }
+    }
+
+    fn add_xdg_output(&mut self, manager: &ZxdgOutputManagerV1) {
+        let xdg_output = manager.get_xdg_output(&self.output);
+        let info_clone = self.info.clone();
+
+        xdg_output.quick_assign(move |_, event, _| {
+            let mut info = info_clone.borrow_mut();
+            match event {
+                zxdg_output_v1::Event::LogicalPosition { x, y } => {
+                    info.position = (x, y);
+                    info.logical_position = true;
+                }
+                zxdg_output_v1::Event::Name { name } => info.name = name,
+                _ => (),
+            }
+        });
+
+        self.xdg_output = Some(xdg_output);
+    }
+
+    fn destroy(&self) {
+        if let Some(xdg_output) = &self.xdg_output {
+            xdg_output.destroy();
+        }
+        // release was added in version 3
+        if self.output.as_ref().version() >= 3 {
+            self.output.release();
+        }
+    }
+
+    fn monitor(&self) -> Monitor {
+        let info = self.info.borrow();
+        Monitor {
+            name: info.name.clone(),
+            position: info.position,
+            size: (info.size.0 as usize, info.size.1 as usize),
+            refresh_rate: info.refresh_rate as u32,
+            scale_factor: info.scale_factor as f32,
+        }
+    }
+}
+
 struct DisplayInfo {
     attached_display: Attached<WlDisplay>,
     surface: Main<WlSurface>,
+    outputs: Outputs,
+    // the outputs the surface is shown on
+    entered_outputs: Rc<RefCell<Vec<WlOutput>>>,
     xdg_surface: Main<XdgSurface>,
     toplevel: Main<XdgToplevel>,
     event_queue: EventQueue,
     xdg_config: Rc<RefCell<Option<u32>>>,
     cursor: wayland_cursor::CursorTheme,
     cursor_surface: Main<WlSurface>,
+    cursor_hotspot: (i32, i32),
+    // buffers for the custom cursors
+    cursor_pool: BufferPool,
     _display: Display,
     buf_pool: BufferPool,
 }
@@ -182,7 +340,37 @@ impl DisplayInfo {
 
         // Access internal WlDisplay with a token
         let attached_display = (*display).clone().attach(event_queue.token());
-        let globals = GlobalManager::new(&attached_display);
+
+        // Outputs can be plugged in and out at any time, so they are tracked from the registry
+        let outputs: Outputs = Rc::new(RefCell::new(Vec::new()));
+        let xdg_output_manager = Rc::new(RefCell::new(None::<Main<ZxdgOutputManagerV1>>));
+        let outputs_clone = outputs.clone();
+        let xdg_output_manager_clone = xdg_output_manager.clone();
+
+        let globals =
+            GlobalManager::new_with_cb(&attached_display, move |event, registry, _| match event {
+                GlobalEvent::New {
+                    id,
+                    interface,
+                    version,
+                } if interface == WlOutput::NAME => {
+                    // Version 4 adds the name of the connector
+                    let mut output = Output::new(id, registry.bind::<WlOutput>(version.min(4), id));
+                    if let Some(manager) = &*xdg_output_manager_clone.borrow() {
+                        output.add_xdg_output(manager);
+                    }
+                    outputs_clone.borrow_mut().push(output);
+                }
+                GlobalEvent::Removed { id, interface } if interface == WlOutput::NAME => {
+                    outputs_clone.borrow_mut().retain(|output| {
+                        if output.global_id == id {
+                            output.destroy();
+                        }
+                        output.global_id != id
+                    });
+                }
+                _ => (),
+            });
 
         // Wait for the Wayland server to process all events
         event_queue
@@ -194,7 +382,43 @@ impl DisplayInfo {
             .instantiate_exact::<WlSeat>(5)
             .map_err(|e| Error::WindowCreate(format!("Failed to retrieve the WlSeat: {:?}", e)))?;
 
-        let input_devices = WaylandInput::new(&seat);
+        // Input methods are only supported if the compositor has text-input-v3
+        let text_input_manager = globals.instantiate_exact::<ZwpTextInputManagerV3>(1).ok();
+
+        // Needed for the clipboard, version 3 adds drag and drop actions
+        let data_device_manager = globals.instantiate_range::<WlDataDeviceManager>(1, 3).ok();
+
+        // Middle click paste is only supported if the compositor has primary-selection
+        let primary_selection_manager = globals
+            .instantiate_exact::<ZwpPrimarySelectionDeviceManagerV1>(1)
+            .ok();
+
+        // Needed for set_cursor_grab and get_mouse_delta
+        let pointer_constraints = globals.instantiate_exact::<ZwpPointerConstraintsV1>(1).ok();
+        let relative_pointer_manager = globals
+            .instantiate_exact::<ZwpRelativePointerManagerV1>(1)
+            .ok();
+
+        // Needed for the pressure and tilt of drawing tablet pens
+        let tablet_manager = globals.instantiate_exact::<ZwpTabletManagerV2>(1).ok();
+
+        // Needed for the position and connector name of the outputs on older compositors
+        if let Ok(manager) = globals.instantiate_range::<ZxdgOutputManagerV1>(1, 3) {
+            for output in outputs.borrow_mut().iter_mut() {
+                output.add_xdg_output(&manager);
+            }
+            *xdg_output_manager.borrow_mut() = Some(manager);
+        }
+
+        let input_devices = WaylandInput::new(
+            &seat,
+            text_input_manager,
+            data_device_manager,
+            primary_selection_manager,
+            pointer_constraints,
+            relative_pointer_manager,
+            tablet_manager,
+        );
         let compositor = globals.instantiate_exact::<WlCompositor>(4).map_err(|e| {
             Error::WindowCreate(format!("Failed to retrieve the compositor: {:?}", e))
         })?;
@@ -204,6 +428,17 @@ impl DisplayInfo {
 
         let surface = compositor.create_surface();
 
+        let entered_outputs = Rc::new(RefCell::new(Vec::new()));
+        let entered_outputs_clone = entered_outputs.clone();
+
+        surface.quick_assign(move |_, event, _| match event {
+            wl_surface::Event::Enter { output } => entered_outputs_clone.borrow_mut().push(output),
+            wl_surface::Event::Leave { output } => entered_outputs_clone
+                .borrow_mut()
+                .retain(|entered: &WlOutput| !entered.as_ref().equals(output.as_ref())),
+            _ => (),
+        });
+
         // Specify format
         let format = if alpha {
             Format::Argb8888
@@ -295,18 +530,23 @@ impl DisplayInfo {
 
         let cursor = wayland_cursor::CursorTheme::load(16, &shm);
         let cursor_surface = compositor.create_surface();
+        let cursor_pool = BufferPool::new(shm.clone(), Format::Argb8888);
 
         Ok((
             Self {
                 _display: display,
                 attached_display,
                 surface,
+                outputs,
+                entered_outputs,
                 xdg_surface,
                 toplevel: xdg_toplevel,
                 event_queue,
                 xdg_config,
                 cursor,
                 cursor_surface,
+                cursor_hotspot: (0, 0),
+                cursor_pool,
                 buf_pool,
             },
             input_devices,
@@ -330,19 +570,100 @@ impl DisplayInfo {
         self.toplevel.set_min_size(size.0, size.1);
     }
 
+    /// Sets the smallest and largest size of the window, 0 means no limit
+    #[inline]
+    fn set_size_limits(&self, min_size: (i32, i32), max_size: (i32, i32)) {
+        self.toplevel.set_min_size(min_size.0, min_size.1);
+        self.toplevel.set_max_size(max_size.0, max_size.1);
+    }
+
+    /// Makes the window fullscreen on the output with the index `monitor`, or on the one the
+    /// compositor picks
+    fn set_fullscreen(&self, fullscreen: bool, monitor: Option<usize>) -> Result<()> {
+        if !fullscreen {
+            self.toplevel.unset_fullscreen();
+            return Ok(());
+        }
+
+        let outputs = self.outputs.borrow();
+        let output = match monitor {
+            Some(index) => Some(outputs.get(index).ok_or_else(|| {
+                Error::NotSupported(format!("There is no monitor with index {}", index))
+            })?),
+            None => None,
+        };
+
+        // the size limits of a window that isn't resizable would keep it from being fullscreen
+        self.toplevel.set_max_size(0, 0);
+        self.toplevel.set_min_size(0, 0);
+        self.toplevel
+            .set_fullscreen(output.map(|output| &**output.output));
+        Ok(())
+    }
+
+    fn monitors(&self) -> Vec<Monitor> {
+        self.outputs.borrow().iter().map(Output::monitor).collect()
+    }
+
+    /// The first output the surface is shown on
+    fn current_monitor(&self) -> Option<Monitor> {
+        let entered_outputs = self.entered_outputs.borrow();
+        let entered = entered_outputs.first()?;
+
+        self.outputs
+            .borrow()
+            .iter()
+            .find(|output| output.output.as_ref().equals(entered.as_ref()))
+            .map(Output::monitor)
+    }
+
     // Sets a specific cursor style
     #[inline]
-    fn update_cursor(&mut self, cursor: &str) -> std::result::Result<(), ()> {
-        let cursor = self.cursor.get_cursor(cursor);
-        if let Some(cursor) = cursor {
+    fn update_cursor(&mut self, cursor: CursorStyle) -> std::result::Result<(), ()> {
+        // use the first name found in the theme, falling back to the arrow
+        let theme = &mut self.cursor;
+        let name = CURSOR_NAMES[cursor as usize]
+            .iter()
+            .chain(CURSOR_NAMES[CursorStyle::Arrow as usize])
+            .find(|name| theme.get_cursor(name).is_some());
+
+        if let Some(cursor) = name.and_then(|name| theme.get_cursor(name)) {
             let img = &cursor[0];
+            let (width, height) = img.dimensions();
+            let (x, y) = img.hotspot();
             self.cursor_surface.attach(Some(img), 0, 0);
-            self.cursor_surface.damage(0, 0, 32, 32);
+            self.cursor_surface
+                .damage(0, 0, width as i32, height as i32);
             self.cursor_surface.commit();
+            self.cursor_hotspot = (x as i32, y as i32);
         }
         Ok(())
     }
 
+    // Sets a cursor image
+    fn update_custom_cursor(&mut self, cursor: &CustomCursor) -> std::io::Result<()> {
+        let size = (cursor.width as i32, cursor.height as i32);
+        let (mut fd, buf) = self.cursor_pool.get_buffer(size)?;
+
+        // Argb8888 is premultiplied
+        let pixels: Vec<u8> = cursor
+            .data
+            .iter()
+            .flat_map(|&pixel| premultiply_alpha(pixel).to_ne_bytes())
+            .collect();
+
+        fd.seek(SeekFrom::Start(0))?;
+        fd.write_all(&pixels)?;
+        fd.flush()?;
+
+        self.cursor_surface.attach(Some(buf), 0, 0);
+        self.cursor_surface.damage(0, 0, size.0, size.1);
+        self.cursor_surface.commit();
+        self.cursor_hotspot = (cursor.hotspot.0 as i32, cursor.hotspot.1 as i32);
+
+        Ok(())
+    }
+
     // Resizes when buffer is bigger or less
     fn update_framebuffer(&mut self, buffer: &[u32], size: (i32, i32)) -> std::io::Result<()> {
         let (mut fd, buf) = self.buf_pool.get_buffer(size)?;
@@ -368,36 +689,71 @@ impl DisplayInfo {
         Ok(())
     }
 
-    fn get_toplevel_info(&self) -> (ToplevelResolution, ToplevelClosed) {
+    fn get_toplevel_info(&self) -> (ToplevelResolution, ToplevelClosed, ToplevelMaximized) {
         let resolution = Rc::new(RefCell::new(None));
         let closed = Rc::new(RefCell::new(false));
+        let maximized = Rc::new(RefCell::new(false));
 
         let resolution_clone = resolution.clone();
         let closed_clone = closed.clone();
+        let maximized_clone = maximized.clone();
 
         self.toplevel.quick_assign(move |_, event, _| {
-            use wayland_protocols::xdg_shell::client::xdg_toplevel::Event;
+            use wayland_protocols::xdg_shell::client::xdg_toplevel::{Event, State};
 
-            if let Event::Configure { width, height, .. } = event {
+            if let Event::Configure {
+                width,
+                height,
+                states,
+            } = event
+            {
                 *resolution_clone.borrow_mut() = Some((width, height));
+
+                // the states are an array of native endian u32
+                *maximized_clone.borrow_mut() = states.chunks_exact(4).any(|state| {
+                    u32::from_ne_bytes(state.try_into().unwrap()) == State::Maximized as u32
+                });
             } else if let Event::Close = event {
                 *closed_clone.borrow_mut() = true;
             }
         });
 
-        (resolution, closed)
+        (resolution, closed, maximized)
     }
 }
 
 struct WaylandInput {
     kb_events: mpsc::Receiver<wl_keyboard::Event>,
     pt_events: mpsc::Receiver<wl_pointer::Event>,
+    ti_events: mpsc::Receiver<zwp_text_input_v3::Event>,
+    dd_events: mpsc::Receiver<wl_data_device::Event>,
+    ps_events: mpsc::Receiver<zwp_primary_selection_device_v1::Event>,
+    rp_events: mpsc::Receiver<zwp_relative_pointer_v1::Event>,
+    tc_events: mpsc::Receiver<wl_touch::Event>,
+    // the events are tagged with whether the tool is an eraser
+    tt_events: mpsc::Receiver<(bool, zwp_tablet_tool_v2::Event)>,
     _keyboard: Main<WlKeyboard>,
     pointer: Main<WlPointer>,
+    text_input: Option<Main<ZwpTextInputV3>>,
+    data_device_manager: Option<Main<WlDataDeviceManager>>,
+    data_device: Option<Main<WlDataDevice>>,
+    primary_selection_manager: Option<Main<ZwpPrimarySelectionDeviceManagerV1>>,
+    primary_selection_device: Option<Main<ZwpPrimarySelectionDeviceV1>>,
+    pointer_constraints: Option<Main<ZwpPointerConstraintsV1>>,
+    _relative_pointer: Option<Main<ZwpRelativePointerV1>>,
+    _tablet_seat: Option<Main<ZwpTabletSeatV2>>,
 }
 
 impl WaylandInput {
-    fn new(seat: &Main<WlSeat>) -> Self {
+    fn new(
+        seat: &Main<WlSeat>,
+        text_input_manager: Option<Main<ZwpTextInputManagerV3>>,
+        data_device_manager: Option<Main<WlDataDeviceManager>>,
+        primary_selection_manager: Option<Main<ZwpPrimarySelectionDeviceManagerV1>>,
+        pointer_constraints: Option<Main<ZwpPointerConstraintsV1>>,
+        relative_pointer_manager: Option<Main<ZwpRelativePointerManagerV1>>,
+        tablet_manager: Option<Main<ZwpTabletManagerV2>>,
+    ) -> Self {
         let (keyboard, pointer) = (seat.get_keyboard(), seat.get_pointer());
         let (kb_sender, kb_receiver) = mpsc::sync_channel(1024);
 
@@ -411,14 +767,158 @@ impl WaylandInput {
             pt_sender.send(event).unwrap();
         });
 
+        let (ti_sender, ti_receiver) = mpsc::sync_channel(1024);
+        let text_input = text_input_manager.map(|manager| {
+            let text_input = manager.get_text_input(seat);
+            text_input.quick_assign(move |_, event, _| {
+                ti_sender.send(event).unwrap();
+            });
+            text_input
+        });
+
+        let (dd_sender, dd_receiver) = mpsc::sync_channel(1024);
+        let data_device = data_device_manager.as_ref().map(|manager| {
+            let data_device = manager.get_data_device(seat);
+            data_device.quick_assign(move |_, event, _| {
+                // The mime types are sent right after the offer is introduced so they have to
+                // be collected from here
+                if let wl_data_device::Event::DataOffer { id } = &event {
+                    init_mime_types(id.as_ref());
+                    id.quick_assign(|offer, event, _| {
+                        if let wl_data_offer::Event::Offer { mime_type } = event {
+                            add_mime_type(offer.as_ref(), mime_type);
+                        }
+                    });
+                }
+
+                dd_sender.send(event).unwrap();
+            });
+            data_device
+        });
+
+        let (ps_sender, ps_receiver) = mpsc::sync_channel(1024);
+        let primary_selection_device = primary_selection_manager.as_ref().map(|manager| {
+            let device = manager.get_device(seat);
+            device.quick_assign(move |_, event, _| {
+                if let zwp_primary_selection_device_v1::Event::DataOffer { offer } = &event {
+                    init_mime_types(offer.as_ref());
+                    offer.quick_assign(|offer, event, _| {
+                        if let zwp_primary_selection_offer_v1::Event::Offer { mime_type } = event {
+                            add_mime_type(offer.as_ref(), mime_type);
+                        }
+                    });
+                }
+
+                ps_sender.send(event).unwrap();
+            });
+            device
+        });
+
+        // Touch can only be requested once the seat has reported that it has touch devices
+        let (tc_sender, tc_receiver) = mpsc::sync_channel(1024);
+        let mut touch: Option<Main<WlTouch>> = None;
+        seat.quick_assign(move |seat, event, _| {
+            if let wl_seat::Event::Capabilities { capabilities } = event {
+                let has_touch = capabilities.contains(wl_seat::Capability::Touch);
+                if has_touch && touch.is_none() {
+                    let tc_sender = tc_sender.clone();
+                    let new_touch = seat.get_touch();
+                    new_touch.quick_assign(move |_, event, _| {
+                        tc_sender.send(event).unwrap();
+                    });
+                    touch = Some(new_touch);
+                } else if !has_touch {
+                    if let Some(touch) = touch.take() {
+                        touch.release();
+                    }
+                }
+            }
+        });
+
+        let (rp_sender, rp_receiver) = mpsc::sync_channel(1024);
+        let relative_pointer = relative_pointer_manager.map(|manager| {
+            let relative_pointer = manager.get_relative_pointer(&pointer);
+            relative_pointer.quick_assign(move |_, event, _| {
+                rp_sender.send(event).unwrap();
+            });
+            relative_pointer
+        });
+
+        let (tt_sender, tt_receiver) = mpsc::sync_channel(1024);
+        let tablet_seat = tablet_manager.map(|manager| {
+            let tablet_seat = manager.get_tablet_seat(seat);
+            tablet_seat.quick_assign(move |_, event, _| {
+                if let zwp_tablet_seat_v2::Event::ToolAdded { id } = event {
+                    let tt_sender = tt_sender.clone();
+                    let mut eraser = false;
+                    id.quick_assign(move |tool, event, _| match event {
+                        // The type is sent once, before the tool is used
+                        zwp_tablet_tool_v2::Event::Type { tool_type } => {
+                            eraser = tool_type == zwp_tablet_tool_v2::Type::Eraser;
+                        }
+                        zwp_tablet_tool_v2::Event::Removed => tool.destroy(),
+                        _ => tt_sender.send((eraser, event)).unwrap(),
+                    });
+                }
+            });
+            tablet_seat
+        });
+
         Self {
             kb_events: kb_receiver,
             pt_events: pt_receiver,
+            ti_events: ti_receiver,
+            dd_events: dd_receiver,
+            ps_events: ps_receiver,
             _keyboard: keyboard,
             pointer,
+            text_input,
+            data_device_manager,
+            data_device,
+            primary_selection_manager,
+            primary_selection_device,
+            rp_events: rp_receiver,
+            tc_events: tc_receiver,
+            tt_events: tt_receiver,
+            pointer_constraints,
+            _relative_pointer: relative_pointer,
+            _tablet_seat: tablet_seat,
         }
     }
 
+    #[inline]
+    fn get_pointer_constraints(
+        &self,
+    ) -> Option<(&Main<ZwpPointerConstraintsV1>, &Main<WlPointer>)> {
+        self.pointer_constraints
+            .as_ref()
+            .map(|constraints| (constraints, &self.pointer))
+    }
+
+    #[inline]
+    fn get_primary_selection_device(
+        &self,
+    ) -> Option<(
+        &Main<ZwpPrimarySelectionDeviceManagerV1>,
+        &Main<ZwpPrimarySelectionDeviceV1>,
+    )> {
+        self.primary_selection_manager
+            .as_ref()
+            .zip(self.primary_selection_device.as_ref())
+    }
+
+    #[inline]
+    fn get_data_device(&self) -> Option<(&Main<WlDataDeviceManager>, &Main<WlDataDevice>)> {
+        self.data_device_manager
+            .as_ref()
+            .zip(self.data_device.as_ref())
+    }
+
+    #[inline]
+    fn get_text_input(&self) -> Option<&Main<ZwpTextInputV3>> {
+        self.text_input.as_ref()
+    }
+
     #[inline]
     fn get_pointer(&self) -> &Main<WlPointer> {
         &self.pointer
@@ -433,6 +933,157 @@ impl WaylandInput {
     fn iter_pointer_events(&self) -> mpsc::TryIter<'_, wl_pointer::Event> {
         self.pt_events.try_iter()
     }
+
+    #[inline]
+    fn iter_relative_pointer_events(&self) -> mpsc::TryIter<'_, zwp_relative_pointer_v1::Event> {
+        self.rp_events.try_iter()
+    }
+
+    #[inline]
+    fn iter_touch_events(&self) -> mpsc::TryIter<'_, wl_touch::Event> {
+        self.tc_events.try_iter()
+    }
+
+    #[inline]
+    fn iter_tablet_tool_events(&self) -> mpsc::TryIter<'_, (bool, zwp_tablet_tool_v2::Event)> {
+        self.tt_events.try_iter()
+    }
+
+    #[inline]
+    fn iter_text_input_events(&self) -> mpsc::TryIter<'_, zwp_text_input_v3::Event> {
+        self.ti_events.try_iter()
+    }
+
+    #[inline]
+    fn iter_data_device_events(&self) -> mpsc::TryIter<'_, wl_data_device::Event> {
+        self.dd_events.try_iter()
+    }
+
+    #[inline]
+    fn iter_primary_selection_events(
+        &self,
+    ) -> mpsc::TryIter<'_, zwp_primary_selection_device_v1::Event> {
+        self.ps_events.try_iter()
+    }
+}
+
+// The mime types of data offers (clipboard and primary selection) are stored in their user data
+
+fn init_mime_types<I>(offer: &Proxy<I>)
+where
+    I: Interface + AsRef<Proxy<I>> + From<Proxy<I>>,
+{
+    offer.user_data().set(|| RefCell::new(Vec::<String>::new()));
+}
+
+fn add_mime_type<I>(offer: &Proxy<I>, mime_type: String)
+where
+    I: Interface + AsRef<Proxy<I>> + From<Proxy<I>>,
+{
+    if let Some(mime_types) = offer.user_data().get::<RefCell<Vec<String>>>() {
+        mime_types.borrow_mut().push(mime_type);
+    }
+}
+
+/// Returns the first of `mime_types` that is offered by a data offer
+fn find_mime_type<I>(offer: &Proxy<I>, mime_types: &[&str]) -> Option<String>
+where
+    I: Interface + AsRef<Proxy<I>> + From<Proxy<I>>,
+{
+    let offered = offer.user_data().get::<RefCell<Vec<String>>>()?.borrow();
+
+    mime_types
+        .iter()
+        .find(|mime_type| offered.iter().any(|m| m == *mime_type))
+        .map(|mime_type| mime_type.to_string())
+}
+
+/// Returns our own data for the first of `mime_types` that we have
+fn find_data(data: &ClipboardData, mime_types: &[&str]) -> Option<Vec<u8>> {
+    mime_types.iter().find_map(|mime_type| {
+        data.iter()
+            .find(|(m, _)| m == mime_type)
+            .map(|(_, bytes)| bytes.clone())
+    })
+}
+
+/// Sends our data to another client asking for it
+fn send_data(data: &ClipboardData, mime_type: &str, fd: RawFd) {
+    // the file is closed when dropped which ends the transfer
+    let mut file = unsafe { File::from_raw_fd(fd) };
+
+    if let Some((_, bytes)) = data.iter().find(|(m, _)| *m == mime_type) {
+        let _ = file.write_all(bytes);
+    }
+}
+
+/// Reads everything from a pipe until the writing end is closed. Gives up if no data arrives
+/// for a while so a misbehaving client can't hang the window.
+fn read_pipe(fd: RawFd) -> Option<Vec<u8>> {
+    let mut file = unsafe { File::from_raw_fd(fd) };
+    let mut data = Vec::new();
+    let mut buffer = [0u8; 4096];
+
+    loop {
+        let mut poll_fd = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+
+        if unsafe { libc::poll(&mut poll_fd, 1, SELECTION_TIMEOUT_MS) } <= 0 {
+            return None;
+        }
+
+        match file.read(&mut buffer) {
+            Ok(0) => return Some(data),
+            Ok(count) => data.extend_from_slice(&buffer[..count]),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(_) => return None,
+        }
+    }
+}
+
+type Preedit = (String, Option<(usize, usize)>);
+
+/// Input method state. The preedit and commit strings are double buffered and applied on
+/// `zwp_text_input_v3::Event::Done`
+#[derive(Default)]
+struct ImeState {
+    enabled: bool,
+    position: (i32, i32),
+    preedit: Preedit,
+    pending_preedit: Preedit,
+    pending_commit: Option<String>,
+}
+
+/// Scrolling collected until the end of a pointer frame, positive values scrolls up and to the
+/// right
+#[derive(Default)]
+struct ScrollFrame {
+    // time of the first axis event, `None` if the frame has no scrolling
+    time: Option<u32>,
+    source: Option<ScrollSource>,
+    pixels: (f32, f32),
+    notches: (f32, f32),
+    discrete: bool,
+}
+
+/// The constraint applied to the pointer by `set_cursor_grab`
+enum PointerConstraint {
+    Confined(Main<ZwpConfinedPointerV1>),
+    Locked(Main<ZwpLockedPointerV1>),
+}
+
+/// A pointer lock used by `set_mouse_pos` to move the cursor, the compositor moves the cursor to
+/// the position hint when the lock is destroyed after it has been activated
+struct PointerWarp {
+    locked: Main<ZwpLockedPointerV1>,
+    // Some(true) when the lock is activated, Some(false) when it ended without moving the cursor
+    state: Rc<RefCell<Option<bool>>>,
+    position: (f32, f32),
+    // the pointer is confined again after the move
+    confined: bool,
 }
 
 pub struct Window {
@@ -451,11 +1102,39 @@ pub struct Window {
     scroll_y: f32,
     buttons: [bool; 8], // Linux kernel defines 8 mouse buttons
     prev_cursor: CursorStyle,
+    custom_cursor: bool,
+    // serial of the last pointer enter, needed for changing the cursor
+    pointer_enter_serial: u32,
+    pointer_focus: bool,
+    pointer_constraint: Option<PointerConstraint>,
+    pointer_warp: Option<PointerWarp>,
+    mouse_delta: (f32, f32),
+    clicks: ClickCounter,
+    touches: Vec<Touch>,
+    #[cfg(all(feature = "gamepad", target_os = "linux"))]
+    gamepads: Gamepads,
+    scroll_frame: ScrollFrame,
+    // the pen state being updated by the tablet tool events and the one of the last frame
+    pen: Option<PenState>,
+    pen_frame: Option<PenState>,
 
     should_close: bool,
+    intercept_close: bool,
+    close_requested: bool,
     active: bool,
 
     key_handler: KeyHandler,
+    events: EventBuffer,
+    ime: ImeState,
+    // serial of the last input event, needed for setting the selection
+    input_serial: u32,
+    selection_offer: Option<WlDataOffer>,
+    primary_selection_offer: Option<ZwpPrimarySelectionOfferV1>,
+    // offer of the files being dragged over the window
+    drag_offer: Option<WlDataOffer>,
+    // sources and data that we serve while we own the selections
+    clipboard: Option<(Main<WlDataSource>, Rc<ClipboardData>)>,
+    primary_selection: Option<(Main<ZwpPrimarySelectionSourceV1>, Rc<ClipboardData>)>,
 
     xkb_context: *mut xkb_ffi::xkb_context,
     xkb_keymap: *mut xkb_ffi::xkb_keymap,
@@ -466,10 +1145,20 @@ pub struct Window {
     menus: Vec<UnixMenu>,
     input: WaylandInput,
     resizable: bool,
+    fullscreen: bool,
+    // the size of the window when it isn't fullscreen (or maximized)
+    windowed_size: (i32, i32),
+    // size set with `set_size`, applied in `update`
+    requested_size: Option<(i32, i32)>,
+    // limits of a resizable window, see `update_size_limits`
+    min_size: Option<(i32, i32)>,
+    max_size: Option<(i32, i32)>,
+    aspect_ratio: Option<(i32, i32)>,
     // Temporary buffer
     buffer: Vec<u32>,
-    // Resolution, closed
-    toplevel_info: (ToplevelResolution, ToplevelClosed),
+    // Resolution, closed, maximized
+    toplevel_info: (ToplevelResolution, ToplevelClosed, ToplevelMaximized),
+    maximized: bool,
     pointer_visibility: bool,
 }
 
@@ -501,7 +1190,7 @@ impl Window {
             display.set_no_resize((width as i32 * scale, height as i32 * scale));
         }
 
-        let (resolution, closed) = display.get_toplevel_info();
+        let (resolution, closed, maximized) = display.get_toplevel_info();
 
         #[cfg(feature = "dlopen")]
         {
@@ -524,7 +1213,7 @@ impl Window {
             ));
         }
 
-        Ok(Self {
+        let mut window = Self {
             display,
 
             width: width as i32 * scale,
@@ -540,11 +1229,34 @@ impl Window {
             scroll_y: 0.,
             buttons: [false; 8],
             prev_cursor: CursorStyle::Arrow,
+            custom_cursor: false,
+            pointer_enter_serial: 0,
+            pointer_focus: false,
+            pointer_warp: None,
+            pointer_constraint: None,
+            mouse_delta: (0., 0.),
+            clicks: ClickCounter::new(),
+            touches: Vec::new(),
+            #[cfg(all(feature = "gamepad", target_os = "linux"))]
+            gamepads: Gamepads::new(),
+            scroll_frame: ScrollFrame::default(),
+            pen: None,
+            pen_frame: None,
 
             should_close: false,
+            intercept_close: opts.intercept_close,
+            close_requested: false,
             active: false,
 
             key_handler: KeyHandler::new(),
+            events: EventBuffer::new(),
+            ime: ImeState::default(),
+            input_serial: 0,
+            selection_offer: None,
+            primary_selection_offer: None,
+            drag_offer: None,
+            clipboard: None,
+            primary_selection: None,
 
             xkb_context: context,
             xkb_keymap: std::ptr::null_mut(),
@@ -555,10 +1267,26 @@ impl Window {
             menus: Vec::new(),
             input,
             resizable: opts.resize && !opts.none,
+            fullscreen: false,
+            windowed_size: (width as i32 * scale, height as i32 * scale),
+            requested_size: None,
+            min_size: None,
+            max_size: None,
+            aspect_ratio: None,
             buffer: Vec::with_capacity(width * height * scale as usize * scale as usize),
-            toplevel_info: (resolution, closed),
+            toplevel_info: (resolution, closed, maximized),
+            maximized: false,
             pointer_visibility: true,
-        })
+        };
+
+        if opts.fullscreen != Fullscreen::Off {
+            window.set_fullscreen(opts.fullscreen)?;
+        }
+        if opts.topmost {
+            window.topmost(true)?;
+        }
+
+        Ok(window)
     }
 
     #[inline]
@@ -581,6 +1309,159 @@ impl Window {
         !self.should_close
     }
 
+    #[inline]
+    pub fn close_requested(&self) -> bool {
+        self.close_requested
+    }
+
+    #[inline]
+    pub fn cancel_close(&mut self) {
+        self.close_requested = false;
+    }
+
+    #[inline]
+    pub fn close(&mut self) {
+        self.close_requested = false;
+        self.should_close = true;
+    }
+
+    #[inline]
+    pub fn get_modifiers(&self) -> Modifiers {
+        self.key_handler.get_modifiers()
+    }
+
+    pub fn set_selection(&mut self, selection: Selection, data: ClipboardData) {
+        let data = Rc::new(data);
+        let source_data = data.clone();
+
+        match selection {
+            Selection::Clipboard => {
+                let (manager, data_device) = match self.input.get_data_device() {
+                    Some(data_device) => data_device,
+                    None => return,
+                };
+
+                let source = manager.create_data_source();
+                for (mime_type, _) in data.iter() {
+                    source.offer(mime_type.to_string());
+                }
+
+                source.quick_assign(move |source, event, _| match event {
+                    wl_data_source::Event::Send { mime_type, fd } => {
+                        send_data(&source_data, &mime_type, fd)
+                    }
+                    wl_data_source::Event::Cancelled => source.destroy(),
+                    _ => {}
+                });
+
+                data_device.set_selection(Some(&source), self.input_serial);
+                self.clipboard = Some((source, data));
+            }
+            Selection::Primary => {
+                let (manager, device) = match self.input.get_primary_selection_device() {
+                    Some(device) => device,
+                    None => return,
+                };
+
+                let source = manager.create_source();
+                for (mime_type, _) in data.iter() {
+                    source.offer(mime_type.to_string());
+                }
+
+                source.quick_assign(move |source, event, _| match event {
+                    zwp_primary_selection_source_v1::Event::Send { mime_type, fd } => {
+                        send_data(&source_data, &mime_type, fd)
+                    }
+                    zwp_primary_selection_source_v1::Event::Cancelled => source.destroy(),
+                    _ => {}
+                });
+
+                device.set_selection(Some(&source), self.input_serial);
+                self.primary_selection = Some((source, data));
+            }
+        }
+    }
+
+    pub fn get_selection(&mut self, selection: Selection, mime_types: &[&str]) -> Option<Vec<u8>> {
+        // we would wait for ourselves to send the data so use it directly
+        match selection {
+            Selection::Clipboard => match &self.clipboard {
+                Some((source, data)) if source.as_ref().is_alive() => {
+                    return find_data(data, mime_types)
+                }
+                _ => {}
+            },
+            Selection::Primary => match &self.primary_selection {
+                Some((source, data)) if source.as_ref().is_alive() => {
+                    return find_data(data, mime_types)
+                }
+                _ => {}
+            },
+        }
+
+        match selection {
+            Selection::Clipboard => {
+                let offer = self.selection_offer.as_ref()?;
+                let mime_type = find_mime_type(offer.as_ref(), mime_types)?;
+                self.receive_selection(|fd| offer.receive(mime_type, fd))
+            }
+            Selection::Primary => {
+                let offer = self.primary_selection_offer.as_ref()?;
+                let mime_type = find_mime_type(offer.as_ref(), mime_types)?;
+                self.receive_selection(|fd| offer.receive(mime_type, fd))
+            }
+        }
+    }
+
+    /// Creates a pipe, passes the write end to `receive` (which should send it to the owner of
+    /// the selection) and reads the data from it
+    fn receive_selection(&self, receive: impl FnOnce(RawFd)) -> Option<Vec<u8>> {
+        let mut fds = [0; 2];
+        if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+            return None;
+        }
+
+        // the write end is duplicated when the request is sent so we can close our copy
+        receive(fds[1]);
+        unsafe { libc::close(fds[1]) };
+        let _ = self.display.event_queue.display().flush();
+
+        read_pipe(fds[0])
+    }
+
+    pub fn set_ime_position(&mut self, x: usize, y: usize) {
+        self.ime.position = (x as i32 * self.scale, y as i32 * self.scale);
+
+        // the position is sent when the text input gets enabled otherwise
+        if self.ime.enabled {
+            if let Some(text_input) = self.input.get_text_input() {
+                let (x, y) = self.ime.position;
+                text_input.set_cursor_rectangle(x, y, 1, 1);
+                text_input.commit();
+            }
+        }
+    }
+
+    #[inline]
+    pub fn is_scancode_down(&self, scancode: Scancode) -> bool {
+        self.key_handler.is_scancode_down(scancode)
+    }
+
+    #[inline]
+    pub fn poll_events(&self) -> std::slice::Iter<'_, TimedEvent> {
+        self.events.iter()
+    }
+
+    #[inline]
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, TimedEvent> {
+        self.events.drain()
+    }
+
+    #[inline]
+    pub fn set_resize_callback(&mut self, callback: Box<dyn FnMut(WindowSize)>) {
+        self.events.set_resize_callback(callback)
+    }
+
     #[inline]
     pub fn get_window_handle(&self) -> *mut c_void {
         self.display.surface.as_ref().c_ptr() as *mut c_void
@@ -653,37 +1534,186 @@ impl Window {
         self.key_handler.is_key_down(key)
     }
 
-    #[inline]
-    pub fn set_position(&mut self, x: isize, y: isize) {
-        self.display
-            .set_geometry((x as i32, y as i32), (self.width, self.height));
+    pub fn topmost(&self, topmost: bool) -> Result<()> {
+        if topmost {
+            // only the layer shell protocol can keep a surface on top, and it's not meant for
+            // application windows (nor available in all compositors)
+            Err(Error::NotSupported(
+                "Wayland doesn't allow windows to stay on top".to_owned(),
+            ))
+        } else {
+            Ok(())
+        }
     }
 
-    #[inline]
-    pub fn get_position(&self) -> (isize, isize) {
-        let (x, y) = (0, 0);
-        // todo!("get_position");
-
-        (x as isize, y as isize)
+    pub fn set_size(&mut self, width: usize, height: usize) {
+        self.requested_size = Some((width.max(1) as i32, height.max(1) as i32));
     }
 
-    #[inline]
-    pub fn set_rate(&mut self, rate: Option<Duration>) {
-        self.update_rate.set_rate(rate);
+    pub fn set_min_size(&mut self, size: Option<(usize, usize)>) {
+        self.min_size = size.map(|(width, height)| (width as i32, height as i32));
+        self.update_size_limits();
     }
 
-    #[inline]
-    pub fn get_delta_time(&self) -> Option<Duration> {
-        self.update_rate.get_delta_time()
+    pub fn set_max_size(&mut self, size: Option<(usize, usize)>) {
+        self.max_size = size.map(|(width, height)| (width as i32, height as i32));
+        self.update_size_limits();
     }
 
-    #[inline]
-    pub fn set_key_repeat_rate(&mut self, rate: f32) {
-        self.key_handler.set_key_repeat_delay(rate);
+    /// There is no protocol for the aspect ratio, instead the configured size is shrunk to it
+    /// (which xdg-shell allows)
+    pub fn set_aspect_ratio(&mut self, ratio: Option<(usize, usize)>) {
+        self.aspect_ratio = ratio
+            .filter(|&(width, height)| width > 0 && height > 0)
+            .map(|(width, height)| (width as i32, height as i32));
     }
 
-    #[inline]
-    pub fn set_key_repeat_delay(&mut self, delay: f32) {
+    /// Tells the compositor the size limits of the window. A window that isn't resizable is kept
+    /// at its size, and there are no limits while it's fullscreen.
+    fn update_size_limits(&self) {
+        if self.fullscreen {
+            return;
+        }
+
+        if self.resizable {
+            self.display.set_size_limits(
+                self.min_size.unwrap_or((0, 0)),
+                self.max_size.unwrap_or((0, 0)),
+            );
+        } else {
+            self.display.set_no_resize(self.windowed_size);
+        }
+    }
+
+    /// Shrinks a size to the aspect ratio set with `set_aspect_ratio`
+    fn fit_aspect_ratio(&self, size: (i32, i32)) -> (i32, i32) {
+        let (ratio_width, ratio_height) = match self.aspect_ratio {
+            Some(ratio) => (ratio.0 as i64, ratio.1 as i64),
+            None => return size,
+        };
+        let (width, height) = (size.0 as i64, size.1 as i64);
+
+        let height_for_width = width * ratio_height / ratio_width;
+        if height_for_width <= height {
+            (width as i32, height_for_width.max(1) as i32)
+        } else {
+            (
+                (height * ratio_width / ratio_height).max(1) as i32,
+                height as i32,
+            )
+        }
+    }
+
+    /// Changes the size of the buffer, the surface gets the new size with the next buffer
+    fn resize(&mut self, size: (i32, i32)) {
+        // Don't try to resize to 0x0
+        if size == (0, 0) || size == (self.width, self.height) {
+            return;
+        }
+
+        self.width = size.0;
+        self.height = size.1;
+        if !self.fullscreen && !self.maximized {
+            self.windowed_size = size;
+        }
+        self.events.push_resize(WindowSize::new(
+            self.width as usize,
+            self.height as usize,
+            self.scale as usize,
+        ));
+    }
+
+    pub fn set_maximized(&mut self, maximized: bool) {
+        if maximized {
+            self.display.toplevel.set_maximized();
+        } else {
+            self.display.toplevel.unset_maximized();
+        }
+    }
+
+    /// Only minimizing is possible, a window can't restore itself
+    pub fn set_minimized(&mut self, minimized: bool) {
+        if minimized {
+            self.display.toplevel.set_minimized();
+        }
+    }
+
+    #[inline]
+    pub fn is_maximized(&self) -> bool {
+        self.maximized
+    }
+
+    #[inline]
+    pub fn monitors(&self) -> Vec<Monitor> {
+        self.display.monitors()
+    }
+
+    /// Wayland only tells which outputs the window is on once it's shown
+    #[inline]
+    pub fn current_monitor(&self) -> Option<Monitor> {
+        self.display.current_monitor()
+    }
+
+    /// xdg-shell doesn't tell if the window is minimized
+    #[inline]
+    pub fn is_minimized(&self) -> bool {
+        false
+    }
+
+    /// Wayland doesn't allow changing the video mode, so exclusive fullscreen is the same as
+    /// borderless fullscreen on the output the compositor picks
+    pub fn set_fullscreen(&mut self, fullscreen: Fullscreen) -> Result<()> {
+        let is_fullscreen = fullscreen != Fullscreen::Off;
+        if !is_fullscreen && !self.fullscreen {
+            return Ok(());
+        }
+
+        let monitor = match fullscreen {
+            Fullscreen::Borderless(monitor) => monitor,
+            _ => None,
+        };
+
+        // the size is changed when the compositor configures the window, see `update`
+        self.display.set_fullscreen(is_fullscreen, monitor)?;
+        if !is_fullscreen {
+            self.update_size_limits();
+        }
+
+        self.fullscreen = is_fullscreen;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_position(&mut self, x: isize, y: isize) {
+        self.display
+            .set_geometry((x as i32, y as i32), (self.width, self.height));
+    }
+
+    #[inline]
+    pub fn get_position(&self) -> (isize, isize) {
+        let (x, y) = (0, 0);
+        // todo!("get_position");
+
+        (x as isize, y as isize)
+    }
+
+    #[inline]
+    pub fn set_rate(&mut self, rate: Option<Duration>) {
+        self.update_rate.set_rate(rate);
+    }
+
+    #[inline]
+    pub fn get_delta_time(&self) -> Option<Duration> {
+        self.update_rate.get_delta_time()
+    }
+
+    #[inline]
+    pub fn set_key_repeat_rate(&mut self, rate: f32) {
+        self.key_handler.set_key_repeat_rate(rate);
+    }
+
+    #[inline]
+    pub fn set_key_repeat_delay(&mut self, delay: f32) {
         self.key_handler.set_key_repeat_delay(delay);
     }
 
@@ -772,17 +1802,49 @@ impl Window {
     }
 
     pub fn update(&mut self) {
+        self.events.clear();
+        #[cfg(all(feature = "gamepad", target_os = "linux"))]
+        self.gamepads.update(&mut self.events);
         self.try_dispatch_events();
 
-        if let Some(resize) = (*self.toplevel_info.0.borrow_mut()).take() {
-            // Don't try to resize to 0x0
-            if self.resizable && resize != (0, 0) {
-                self.width = resize.0;
-                self.height = resize.1;
+        let maximized = *self.toplevel_info.2.borrow();
+        if maximized != self.maximized {
+            self.maximized = maximized;
+            self.events.push(Event::Maximized(maximized));
+        }
+
+        let configured_size = self.toplevel_info.0.borrow_mut().take();
+        if let Some(resize) = configured_size {
+            // 0x0 leaves the size up to us, which is the windowed size when leaving fullscreen
+            let resize = if self.fullscreen || (self.maximized && resize != (0, 0)) {
+                resize
+            } else if self.resizable && resize != (0, 0) {
+                self.fit_aspect_ratio(resize)
+            } else {
+                self.windowed_size
+            };
+            self.resize(resize);
+        }
+
+        if let Some(size) = self.requested_size.take() {
+            if self.fullscreen || self.maximized {
+                // used when the window goes back to normal
+                self.windowed_size = size;
+            } else {
+                self.resize(size);
+                self.update_size_limits();
             }
         }
-        if *self.toplevel_info.1.borrow() {
-            self.should_close = true;
+
+        // Reset the flag so a new close request can be detected if this one gets cancelled
+        if self.toplevel_info.1.replace(false) && !self.should_close {
+            self.events.push(Event::CloseRequested);
+
+            if self.intercept_close {
+                self.close_requested = true;
+            } else {
+                self.should_close = true;
+            }
         }
 
         self.key_handler.update();
@@ -796,21 +1858,43 @@ impl Window {
                     self.xkb_keymap = keymap;
                     self.xkb_state = unsafe { ffi_dispatch!(XKBH, xkb_state_new, keymap) };
                 }
-                Event::Enter { .. } => {
+                Event::Enter { serial, .. } => {
+                    self.input_serial = serial;
                     self.active = true;
+                    self.events.push(crate::Event::Focus(true));
                 }
                 Event::Leave { .. } => {
                     self.active = false;
+                    self.events.push(crate::Event::Focus(false));
+                    self.key_handler.cancel_repeat();
                 }
-                Event::Key { key, state, .. } => {
-                    if !self.xkb_state.is_null() {
-                        Self::handle_key(
-                            self.xkb_state,
-                            key + KEY_XKB_OFFSET,
-                            state,
-                            &mut self.key_handler,
-                        );
-                    }
+                Event::Key {
+                    serial,
+                    key,
+                    state,
+                    time,
+                } if !self.xkb_state.is_null() => {
+                    self.input_serial = serial;
+                    self.events.set_time_ms(time);
+                    Self::handle_key(
+                        self.xkb_state,
+                        self.xkb_keymap,
+                        key,
+                        state,
+                        false,
+                        &mut self.key_handler,
+                        &mut self.events,
+                    );
+                }
+                Event::RepeatInfo { rate, delay } => {
+                    // a rate of 0 disables repeat
+                    let delay = if rate > 0 {
+                        delay as f32 / 1000.0
+                    } else {
+                        f32::INFINITY
+                    };
+                    self.key_handler
+                        .set_system_key_repeat(delay, 1.0 / rate.max(1) as f32);
                 }
                 Event::Modifiers {
                     mods_depressed,
@@ -818,21 +1902,193 @@ impl Window {
                     mods_locked,
                     group,
                     ..
+                } if !self.xkb_state.is_null() => {
+                    unsafe {
+                        ffi_dispatch!(
+                            XKBH,
+                            xkb_state_update_mask,
+                            self.xkb_state,
+                            mods_depressed,
+                            mods_latched,
+                            mods_locked,
+                            0,
+                            0,
+                            group
+                        )
+                    };
+
+                    let modifiers = Self::get_xkb_modifiers(self.xkb_state);
+                    self.key_handler.set_modifiers(modifiers);
+                }
+                _ => {}
+            }
+        }
+
+        // Wayland leaves repeating keys to the client
+        while let Some((_, scancode, time)) = self.key_handler.next_repeat() {
+            self.events.set_time_ms(time);
+            Self::handle_key(
+                self.xkb_state,
+                self.xkb_keymap,
+                scancode.0,
+                wl_keyboard::KeyState::Pressed,
+                true,
+                &mut self.key_handler,
+                &mut self.events,
+            );
+        }
+
+        for event in self.input.iter_data_device_events() {
+            use wl_data_device::Event;
+
+            match event {
+                Event::Selection { id } => {
+                    // the previous offer is no longer valid
+                    if let Some(offer) = self.selection_offer.take() {
+                        offer.destroy();
+                    }
+                    self.selection_offer = id;
+                }
+                Event::Enter {
+                    serial, x, y, id, ..
                 } => {
-                    if !self.xkb_state.is_null() {
-                        unsafe {
-                            ffi_dispatch!(
-                                XKBH,
-                                xkb_state_update_mask,
-                                self.xkb_state,
-                                mods_depressed,
-                                mods_latched,
-                                mods_locked,
-                                0,
-                                0,
-                                group
-                            )
-                        };
+                    if let Some(offer) = self.drag_offer.take() {
+                        offer.destroy();
+                    }
+
+                    let offer = match id {
+                        Some(offer) => offer,
+                        None => continue,
+                    };
+
+                    // only files are accepted
+                    if find_mime_type(offer.as_ref(), &[URI_LIST_MIME_TYPE]).is_none() {
+                        offer.accept(serial, None);
+                        offer.destroy();
+                        continue;
+                    }
+
+                    offer.accept(serial, Some(URI_LIST_MIME_TYPE.to_string()));
+                    if offer.as_ref().version() >= 3 {
+                        offer.set_actions(DndAction::Copy, DndAction::Copy);
+                    }
+
+                    self.drag_offer = Some(offer);
+                    self.events.push(crate::Event::DragEnter {
+                        x: x as f32 / self.scale as f32,
+                        y: y as f32 / self.scale as f32,
+                    });
+                }
+                Event::Motion { time, x, y } if self.drag_offer.is_some() => {
+                    self.events.set_time_ms(time);
+                    self.events.push(crate::Event::DragMove {
+                        x: x as f32 / self.scale as f32,
+                        y: y as f32 / self.scale as f32,
+                    });
+                }
+                Event::Leave => {
+                    if let Some(offer) = self.drag_offer.take() {
+                        offer.destroy();
+                        self.events.push(crate::Event::DragLeave);
+                    }
+                }
+                Event::Drop => {
+                    if let Some(offer) = self.drag_offer.take() {
+                        let paths = self
+                            .receive_selection(|fd| {
+                                offer.receive(URI_LIST_MIME_TYPE.to_string(), fd)
+                            })
+                            .map(|uri_list| parse_uri_list(&uri_list))
+                            .unwrap_or_default();
+
+                        if offer.as_ref().version() >= 3 {
+                            offer.finish();
+                        }
+                        offer.destroy();
+
+                        if paths.is_empty() {
+                            self.events.push(crate::Event::DragLeave);
+                        } else {
+                            self.events.push(crate::Event::Drop(paths));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for event in self.input.iter_primary_selection_events() {
+            if let zwp_primary_selection_device_v1::Event::Selection { id } = event {
+                if let Some(offer) = self.primary_selection_offer.take() {
+                    offer.destroy();
+                }
+                self.primary_selection_offer = id;
+            }
+        }
+
+        for event in self.input.iter_text_input_events() {
+            use zwp_text_input_v3::Event;
+
+            match event {
+                Event::Enter { .. } => {
+                    if let Some(text_input) = self.input.get_text_input() {
+                        let (x, y) = self.ime.position;
+                        text_input.enable();
+                        text_input.set_cursor_rectangle(x, y, 1, 1);
+                        text_input.commit();
+                    }
+                    self.ime.enabled = true;
+                }
+                Event::Leave { .. } => {
+                    if let Some(text_input) = self.input.get_text_input() {
+                        text_input.disable();
+                        text_input.commit();
+                    }
+                    self.ime.enabled = false;
+
+                    if !self.ime.preedit.0.is_empty() {
+                        self.ime.preedit = Preedit::default();
+                        self.events.push(crate::Event::Preedit {
+                            text: String::new(),
+                            cursor: None,
+                        });
+                    }
+                }
+                Event::PreeditString {
+                    text,
+                    cursor_begin,
+                    cursor_end,
+                } => {
+                    let cursor = if cursor_begin < 0 || cursor_end < 0 {
+                        None
+                    } else {
+                        Some((cursor_begin as usize, cursor_end as usize))
+                    };
+                    self.ime.pending_preedit = (text.unwrap_or_default(), cursor);
+                }
+                Event::CommitString { text } => {
+                    self.ime.pending_commit = text;
+                }
+                Event::Done { .. } => {
+                    if let Some(text) = self.ime.pending_commit.take() {
+                        self.events.push(crate::Event::Commit(text.clone()));
+
+                        for c in text.chars() {
+                            self.events.push(crate::Event::Char(c));
+
+                            if let Some(callback) = &mut self.key_handler.key_callback {
+                                callback.add_char(c as u32);
+                            }
+                        }
+                    }
+
+                    let preedit = std::mem::take(&mut self.ime.pending_preedit);
+                    if preedit != self.ime.preedit {
+                        self.ime.preedit = preedit.clone();
+                        self.events.push(crate::Event::Preedit {
+                            text: preedit.0,
+                            cursor: preedit.1,
+                        });
                     }
                 }
                 _ => {}
@@ -841,6 +2097,19 @@ impl Window {
 
         self.scroll_x = 0.;
         self.scroll_y = 0.;
+        self.mouse_delta = (0., 0.);
+
+        for event in self.input.iter_relative_pointer_events() {
+            if let zwp_relative_pointer_v1::Event::RelativeMotion {
+                dx_unaccel,
+                dy_unaccel,
+                ..
+            } = event
+            {
+                self.mouse_delta.0 += dx_unaccel as f32;
+                self.mouse_delta.1 += dy_unaccel as f32;
+            }
+        }
 
         for event in self.input.iter_pointer_events() {
             use wayland_client::protocol::wl_pointer::Event;
@@ -854,89 +2123,122 @@ impl Window {
                 } => {
                     self.mouse_x = surface_x as f32;
                     self.mouse_y = surface_y as f32;
+                    self.events.push(crate::Event::MouseMove {
+                        x: self.mouse_x / self.scale as f32,
+                        y: self.mouse_y / self.scale as f32,
+                    });
 
-                    self.input.get_pointer().set_cursor(
-                        serial,
-                        Some(&self.display.cursor_surface),
-                        0,
-                        0,
-                    );
-                    self.display
-                        .update_cursor(Self::decode_cursor(self.prev_cursor))
-                        .unwrap();
-
-                    if self.pointer_visibility {
-                        self.input.get_pointer().set_cursor(
-                            serial,
-                            Some(&self.display.cursor_surface),
-                            0,
-                            0,
-                        );
-                    } else {
-                        self.input.get_pointer().set_cursor(serial, None, 0, 0);
+                    if !self.custom_cursor {
+                        self.display.update_cursor(self.prev_cursor).unwrap();
                     }
+
+                    self.pointer_enter_serial = serial;
+                    self.pointer_focus = true;
+                    self.set_pointer_cursor(serial);
                 }
                 Event::Motion {
+                    time,
                     surface_x,
                     surface_y,
-                    ..
                 } => {
                     self.mouse_x = surface_x as f32;
                     self.mouse_y = surface_y as f32;
+                    self.events.set_time_ms(time);
+                    self.events.push(crate::Event::MouseMove {
+                        x: self.mouse_x / self.scale as f32,
+                        y: self.mouse_y / self.scale as f32,
+                    });
                 }
                 Event::Button {
                     button,
                     state,
                     serial,
-                    ..
+                    time,
                 } => {
+                    self.input_serial = serial;
                     use wayland_client::protocol::wl_pointer::ButtonState;
 
                     let pressed = state == ButtonState::Pressed;
 
-                    match button {
+                    let button = match button {
                         // Left mouse button
-                        KEY_MOUSE_BTN1 => self.buttons[0] = pressed,
+                        KEY_MOUSE_BTN1 => Some((0, MouseButton::Left)),
                         // Right mouse button
-                        KEY_MOUSE_BTN2 => self.buttons[1] = pressed,
+                        KEY_MOUSE_BTN2 => Some((1, MouseButton::Right)),
                         // Middle mouse button
-                        KEY_MOUSE_BTN3 => self.buttons[2] = pressed,
+                        KEY_MOUSE_BTN3 => Some((2, MouseButton::Middle)),
                         // Back mouse button
-                        KEY_MOUSE_BTN8 => self.buttons[3] = pressed,
+                        KEY_MOUSE_BTN8 => Some((3, MouseButton::Back)),
                         // Forward mouse button
-                        KEY_MOUSE_BTN9 => self.buttons[4] = pressed,
-                        _ => (
-                            // TODO: handle more mouse buttons (see: linux/input-event-codes.h from
-                            // the Linux kernel)
-                        ),
+                        KEY_MOUSE_BTN9 => Some((4, MouseButton::Forward)),
+                        // TODO: handle more mouse buttons (see: linux/input-event-codes.h from
+                        // the Linux kernel)
+                        _ => None,
+                    };
+
+                    if let Some((index, button)) = button {
+                        self.buttons[index] = pressed;
+                        let clicks = if pressed {
+                            self.clicks.press(button, time, self.mouse_x, self.mouse_y)
+                        } else {
+                            self.clicks.release(button)
+                        };
+                        self.events.set_time_ms(time);
+                        self.events.push(crate::Event::MouseButton {
+                            button,
+                            pressed,
+                            clicks,
+                        });
                     }
 
-                    if self.pointer_visibility {
-                        self.input.get_pointer().set_cursor(
-                            serial,
-                            Some(&self.display.cursor_surface),
-                            0,
-                            0,
-                        );
-                    } else {
-                        self.input.get_pointer().set_cursor(serial, None, 0, 0);
-                    }
+                    self.set_pointer_cursor(serial);
                 }
-                Event::Axis { axis, value, .. } => {
+                Event::Axis { axis, value, time } => {
                     use wayland_client::protocol::wl_pointer::Axis;
 
+                    let value = value as f32;
                     match axis {
-                        Axis::VerticalScroll => self.scroll_y = value as f32,
-                        Axis::HorizontalScroll => self.scroll_x = value as f32,
-                        _ => {}
+                        Axis::VerticalScroll => {
+                            self.scroll_y = value;
+                            self.scroll_frame.pixels.1 -= value;
+                        }
+                        Axis::HorizontalScroll => {
+                            self.scroll_x = value;
+                            self.scroll_frame.pixels.0 += value;
+                        }
+                        _ => continue,
                     }
+
+                    self.scroll_frame.time.get_or_insert(time);
                 }
                 Event::Frame => {
-                    // TODO
+                    let frame = std::mem::take(&mut self.scroll_frame);
+                    if let Some(time) = frame.time {
+                        let notches = if frame.discrete {
+                            frame.notches
+                        } else {
+                            (
+                                frame.pixels.0 / PIXELS_PER_NOTCH,
+                                frame.pixels.1 / PIXELS_PER_NOTCH,
+                            )
+                        };
+
+                        self.events.set_time_ms(time);
+                        self.events.push(crate::Event::Scroll {
+                            source: frame.source.unwrap_or(ScrollSource::Continuous),
+                            pixels: frame.pixels,
+                            notches,
+                        });
+                    }
                 }
                 Event::AxisSource { axis_source } => {
-                    let _ = axis_source;
-                    // TODO
+                    use wayland_client::protocol::wl_pointer::AxisSource;
+
+                    self.scroll_frame.source = match axis_source {
+                        AxisSource::Wheel | AxisSource::WheelTilt => Some(ScrollSource::Wheel),
+                        AxisSource::Finger => Some(ScrollSource::Finger),
+                        _ => Some(ScrollSource::Continuous),
+                    };
                 }
                 Event::AxisStop { axis, .. } => {
                     use wayland_client::protocol::wl_pointer::Axis;
@@ -948,19 +2250,73 @@ impl Window {
                     }
                 }
                 Event::AxisDiscrete { axis, discrete } => {
-                    let _ = (axis, discrete);
-                    // TODO
+                    use wayland_client::protocol::wl_pointer::Axis;
+
+                    let discrete = discrete as f32;
+                    match axis {
+                        Axis::VerticalScroll => self.scroll_frame.notches.1 -= discrete,
+                        Axis::HorizontalScroll => self.scroll_frame.notches.0 += discrete,
+                        _ => continue,
+                    }
+
+                    self.scroll_frame.discrete = true;
                 }
                 Event::Leave { serial, .. } => {
-                    if self.pointer_visibility {
-                        self.input.get_pointer().set_cursor(
-                            serial,
-                            Some(&self.display.cursor_surface),
-                            0,
-                            0,
-                        );
-                    } else {
-                        self.input.get_pointer().set_cursor(serial, None, 0, 0);
+                    self.pointer_focus = false;
+                    self.set_pointer_cursor(serial);
+                }
+                _ => {}
+            }
+        }
+
+        self.process_pointer_warp();
+        self.process_tablet_tool_events();
+
+        for event in self.input.iter_touch_events() {
+            use wayland_client::protocol::wl_touch::Event;
+
+            let scale = self.scale as f32;
+            match event {
+                Event::Down {
+                    serial,
+                    time,
+                    id,
+                    x,
+                    y,
+                    ..
+                } => {
+                    self.input_serial = serial;
+                    let touch = Touch {
+                        id: id as u32 as u64,
+                        x: x as f32 / scale,
+                        y: y as f32 / scale,
+                    };
+                    self.touches.push(touch);
+                    self.events.set_time_ms(time);
+                    Self::push_touch(&mut self.events, touch, TouchPhase::Begin);
+                }
+                Event::Motion { time, id, x, y } => {
+                    let id = id as u32 as u64;
+                    if let Some(touch) = self.touches.iter_mut().find(|touch| touch.id == id) {
+                        touch.x = x as f32 / scale;
+                        touch.y = y as f32 / scale;
+                        let touch = *touch;
+                        self.events.set_time_ms(time);
+                        Self::push_touch(&mut self.events, touch, TouchPhase::Move);
+                    }
+                }
+                Event::Up { time, id, .. } => {
+                    let id = id as u32 as u64;
+                    if let Some(index) = self.touches.iter().position(|touch| touch.id == id) {
+                        let touch = self.touches.remove(index);
+                        self.events.set_time_ms(time);
+                        Self::push_touch(&mut self.events, touch, TouchPhase::End);
+                    }
+                }
+                // cancel applies to all touches
+                Event::Cancel => {
+                    for touch in std::mem::take(&mut self.touches) {
+                        Self::push_touch(&mut self.events, touch, TouchPhase::Cancel);
                     }
                 }
                 _ => {}
@@ -968,145 +2324,321 @@ impl Window {
         }
     }
 
+    fn process_tablet_tool_events(&mut self) {
+        // collected as the frames have to update the window
+        let events: Vec<_> = self.input.iter_tablet_tool_events().collect();
+        for (eraser, event) in events {
+            use wayland_protocols::unstable::tablet::v2::client::zwp_tablet_tool_v2::{
+                ButtonState, Event,
+            };
+
+            match event {
+                Event::ProximityIn { .. } => {
+                    self.pen = Some(PenState {
+                        eraser,
+                        ..PenState::default()
+                    })
+                }
+                Event::ProximityOut => self.pen = None,
+                _ => (),
+            }
+
+            let pen = match &mut self.pen {
+                Some(pen) => pen,
+                None => continue,
+            };
+
+            match event {
+                Event::Down { serial } => {
+                    self.input_serial = serial;
+                    pen.in_contact = true;
+                }
+                Event::Up => pen.in_contact = false,
+                Event::Motion { x, y } => {
+                    pen.x = x as f32 / self.scale as f32;
+                    pen.y = y as f32 / self.scale as f32;
+                }
+                Event::Pressure { pressure } => pen.pressure = pressure as f32 / 65535.0,
+                Event::Tilt { tilt_x, tilt_y } => {
+                    pen.tilt_x = tilt_x as f32;
+                    pen.tilt_y = tilt_y as f32;
+                }
+                Event::Button { button, state, .. } => {
+                    let pressed = state == ButtonState::Pressed;
+                    match button {
+                        KEY_STYLUS_BTN1 => pen.barrel_buttons[0] = pressed,
+                        KEY_STYLUS_BTN2 => pen.barrel_buttons[1] = pressed,
+                        _ => (),
+                    }
+                }
+                Event::Frame { time } => self.process_pen_frame(time),
+                _ => (),
+            }
+        }
+
+        // proximity out is followed by a frame but the pen is gone by then
+        if self.pen.is_none() && self.pen_frame.is_some() {
+            self.pen_frame = None;
+            self.events.push(crate::Event::PenLeave);
+        }
+    }
+
+    /// Reports the changes of the pen since the previous frame. The compositor doesn't move
+    /// the pointer for the pen when tablets are used, so the mouse is updated here as well.
+    fn process_pen_frame(&mut self, time: u32) {
+        let pen = match self.pen {
+            Some(pen) if self.pen_frame != Some(pen) => pen,
+            _ => return,
+        };
+        let previous = self.pen_frame.replace(pen).unwrap_or_default();
+
+        self.events.set_time_ms(time);
+        self.events.push(crate::Event::Pen(pen));
+
+        let scale = self.scale as f32;
+        if (pen.x, pen.y) != (previous.x, previous.y) {
+            self.mouse_x = pen.x * scale;
+            self.mouse_y = pen.y * scale;
+            self.events
+                .push(crate::Event::MouseMove { x: pen.x, y: pen.y });
+        }
+
+        if pen.in_contact != previous.in_contact {
+            self.buttons[0] = pen.in_contact;
+            let clicks = if pen.in_contact {
+                let (x, y) = (self.mouse_x, self.mouse_y);
+                self.clicks.press(MouseButton::Left, time, x, y)
+            } else {
+                self.clicks.release(MouseButton::Left)
+            };
+            self.events.push(crate::Event::MouseButton {
+                button: MouseButton::Left,
+                pressed: pen.in_contact,
+                clicks,
+            });
+        }
+    }
+
+    #[inline]
+    fn push_touch(events: &mut EventBuffer, touch: Touch, phase: TouchPhase) {
+        events.push(crate::Event::Touch {
+            id: touch.id,
+            phase,
+            x: touch.x,
+            y: touch.y,
+        });
+    }
+
     fn handle_key(
         keymap_state: *mut xkb_ffi::xkb_state,
-        key: u32,
+        keymap: *mut xkb_ffi::xkb_keymap,
+        scancode: u32,
         state: wl_keyboard::KeyState,
+        repeat: bool,
         key_handler: &mut KeyHandler,
+        events: &mut EventBuffer,
     ) {
+        use super::xkb_keysyms as key;
+
         let is_down = state == wl_keyboard::KeyState::Pressed;
-        let key_xkb = unsafe { ffi_dispatch!(XKBH, xkb_state_key_get_one_sym, keymap_state, key) };
-        if key_xkb != 0 {
-            use super::xkb_keysyms as key;
-
-            if state == wl_keyboard::KeyState::Pressed {
-                // Taken from GLFW
-                let code_point = unsafe { ffi_dispatch!(XKBH, xkb_keysym_to_utf32, key_xkb) };
-                if !(code_point < 32 || (code_point > 126 && code_point < 160)) {
-                    if let Some(ref mut callback) = key_handler.key_callback {
-                        callback.add_char(code_point);
-                    }
+        let key_xkb = unsafe {
+            ffi_dispatch!(
+                XKBH,
+                xkb_state_key_get_one_sym,
+                keymap_state,
+                scancode + KEY_XKB_OFFSET
+            )
+        };
+
+        if key_xkb != 0 && is_down {
+            // Taken from GLFW
+            let code_point = unsafe { ffi_dispatch!(XKBH, xkb_keysym_to_utf32, key_xkb) };
+            if !(code_point < 32 || (code_point > 126 && code_point < 160)) {
+                if let Some(c) = char::from_u32(code_point) {
+                    events.push(Event::Commit(c.to_string()));
+                    events.push(Event::Char(c));
+                }
+                if let Some(ref mut callback) = key_handler.key_callback {
+                    callback.add_char(code_point);
                 }
             }
+        }
 
-            let key_i = match key_xkb {
-                key::XKB_KEY_0 => Key::Key0,
-                key::XKB_KEY_1 => Key::Key1,
-                key::XKB_KEY_2 => Key::Key2,
-                key::XKB_KEY_3 => Key::Key3,
-                key::XKB_KEY_4 => Key::Key4,
-                key::XKB_KEY_5 => Key::Key5,
-                key::XKB_KEY_6 => Key::Key6,
-                key::XKB_KEY_7 => Key::Key7,
-                key::XKB_KEY_8 => Key::Key8,
-                key::XKB_KEY_9 => Key::Key9,
-
-                key::XKB_KEY_a => Key::A,
-                key::XKB_KEY_b => Key::B,
-                key::XKB_KEY_c => Key::C,
-                key::XKB_KEY_d => Key::D,
-                key::XKB_KEY_e => Key::E,
-                key::XKB_KEY_f => Key::F,
-                key::XKB_KEY_g => Key::G,
-                key::XKB_KEY_h => Key::H,
-                key::XKB_KEY_i => Key::I,
-                key::XKB_KEY_j => Key::J,
-                key::XKB_KEY_k => Key::K,
-                key::XKB_KEY_l => Key::L,
-                key::XKB_KEY_m => Key::M,
-                key::XKB_KEY_n => Key::N,
-                key::XKB_KEY_o => Key::O,
-                key::XKB_KEY_p => Key::P,
-                key::XKB_KEY_q => Key::Q,
-                key::XKB_KEY_r => Key::R,
-                key::XKB_KEY_s => Key::S,
-                key::XKB_KEY_t => Key::T,
-                key::XKB_KEY_u => Key::U,
-                key::XKB_KEY_v => Key::V,
-                key::XKB_KEY_w => Key::W,
-                key::XKB_KEY_x => Key::X,
-                key::XKB_KEY_y => Key::Y,
-                key::XKB_KEY_z => Key::Z,
-
-                key::XKB_KEY_apostrophe => Key::Apostrophe,
-                key::XKB_KEY_grave => Key::Backquote,
-                key::XKB_KEY_backslash => Key::Backslash,
-                key::XKB_KEY_comma => Key::Comma,
-                key::XKB_KEY_equal => Key::Equal,
-                key::XKB_KEY_bracketleft => Key::LeftBracket,
-                key::XKB_KEY_bracketright => Key::RightBracket,
-                key::XKB_KEY_minus => Key::Minus,
-                key::XKB_KEY_period => Key::Period,
-                key::XKB_KEY_semicolon => Key::Semicolon,
-                key::XKB_KEY_slash => Key::Slash,
-                key::XKB_KEY_space => Key::Space,
-
-                key::XKB_KEY_F1 => Key::F1,
-                key::XKB_KEY_F2 => Key::F2,
-                key::XKB_KEY_F3 => Key::F3,
-                key::XKB_KEY_F4 => Key::F4,
-                key::XKB_KEY_F5 => Key::F5,
-                key::XKB_KEY_F6 => Key::F6,
-                key::XKB_KEY_F7 => Key::F7,
-                key::XKB_KEY_F8 => Key::F8,
-                key::XKB_KEY_F9 => Key::F9,
-                key::XKB_KEY_F10 => Key::F10,
-                key::XKB_KEY_F11 => Key::F11,
-                key::XKB_KEY_F12 => Key::F12,
-
-                key::XKB_KEY_Down => Key::Down,
-                key::XKB_KEY_Left => Key::Left,
-                key::XKB_KEY_Right => Key::Right,
-                key::XKB_KEY_Up => Key::Up,
-                key::XKB_KEY_Escape => Key::Escape,
-                key::XKB_KEY_BackSpace => Key::Backspace,
-                key::XKB_KEY_Delete => Key::Delete,
-                key::XKB_KEY_End => Key::End,
-                key::XKB_KEY_Return => Key::Enter,
-                key::XKB_KEY_Home => Key::Home,
-                key::XKB_KEY_Insert => Key::Insert,
-                key::XKB_KEY_Menu => Key::Menu,
-                key::XKB_KEY_Page_Down => Key::PageDown,
-                key::XKB_KEY_Page_Up => Key::PageUp,
-                key::XKB_KEY_Pause => Key::Pause,
-                key::XKB_KEY_Tab => Key::Tab,
-                key::XKB_KEY_Num_Lock => Key::NumLock,
-                key::XKB_KEY_Caps_Lock => Key::CapsLock,
-                key::XKB_KEY_Scroll_Lock => Key::ScrollLock,
-                key::XKB_KEY_Shift_L => Key::LeftShift,
-                key::XKB_KEY_Shift_R => Key::RightShift,
-                key::XKB_KEY_Alt_L => Key::LeftAlt,
-                key::XKB_KEY_Alt_R => Key::RightAlt,
-                key::XKB_KEY_Control_L => Key::LeftCtrl,
-                key::XKB_KEY_Control_R => Key::RightCtrl,
-                key::XKB_KEY_Super_L => Key::LeftSuper,
-                key::XKB_KEY_Super_R => Key::RightSuper,
-
-                key::XKB_KEY_KP_Insert => Key::NumPad0,
-                key::XKB_KEY_KP_End => Key::NumPad1,
-                key::XKB_KEY_KP_Down => Key::NumPad2,
-                key::XKB_KEY_KP_Next => Key::NumPad3,
-                key::XKB_KEY_KP_Left => Key::NumPad4,
-                key::XKB_KEY_KP_Begin => Key::NumPad5,
-                key::XKB_KEY_KP_Right => Key::NumPad6,
-                key::XKB_KEY_KP_Home => Key::NumPad7,
-                key::XKB_KEY_KP_Up => Key::NumPad8,
-                key::XKB_KEY_KP_Prior => Key::NumPad9,
-                key::XKB_KEY_KP_Decimal => Key::NumPadDot,
-                key::XKB_KEY_KP_Divide => Key::NumPadSlash,
-                key::XKB_KEY_KP_Multiply => Key::NumPadAsterisk,
-                key::XKB_KEY_KP_Subtract => Key::NumPadMinus,
-                key::XKB_KEY_KP_Add => Key::NumPadPlus,
-                key::XKB_KEY_KP_Enter => Key::NumPadEnter,
-
-                _ => {
-                    // Ignore other keys
-                    return;
-                }
+        let key_i = match key_xkb {
+            key::XKB_KEY_0 => Key::Key0,
+            key::XKB_KEY_1 => Key::Key1,
+            key::XKB_KEY_2 => Key::Key2,
+            key::XKB_KEY_3 => Key::Key3,
+            key::XKB_KEY_4 => Key::Key4,
+            key::XKB_KEY_5 => Key::Key5,
+            key::XKB_KEY_6 => Key::Key6,
+            key::XKB_KEY_7 => Key::Key7,
+            key::XKB_KEY_8 => Key::Key8,
+            key::XKB_KEY_9 => Key::Key9,
+
+            key::XKB_KEY_a => Key::A,
+            key::XKB_KEY_b => Key::B,
+            key::XKB_KEY_c => Key::C,
+            key::XKB_KEY_d => Key::D,
+            key::XKB_KEY_e => Key::E,
+            key::XKB_KEY_f => Key::F,
+            key::XKB_KEY_g => Key::G,
+            key::XKB_KEY_h => Key::H,
+            key::XKB_KEY_i => Key::I,
+            key::XKB_KEY_j => Key::J,
+            key::XKB_KEY_k => Key::K,
+            key::XKB_KEY_l => Key::L,
+            key::XKB_KEY_m => Key::M,
+            key::XKB_KEY_n => Key::N,
+            key::XKB_KEY_o => Key::O,
+            key::XKB_KEY_p => Key::P,
+            key::XKB_KEY_q => Key::Q,
+            key::XKB_KEY_r => Key::R,
+            key::XKB_KEY_s => Key::S,
+            key::XKB_KEY_t => Key::T,
+            key::XKB_KEY_u => Key::U,
+            key::XKB_KEY_v => Key::V,
+            key::XKB_KEY_w => Key::W,
+            key::XKB_KEY_x => Key::X,
+            key::XKB_KEY_y => Key::Y,
+            key::XKB_KEY_z => Key::Z,
+
+            key::XKB_KEY_apostrophe => Key::Apostrophe,
+            key::XKB_KEY_grave => Key::Backquote,
+            key::XKB_KEY_backslash => Key::Backslash,
+            key::XKB_KEY_comma => Key::Comma,
+            key::XKB_KEY_equal => Key::Equal,
+            key::XKB_KEY_bracketleft => Key::LeftBracket,
+            key::XKB_KEY_bracketright => Key::RightBracket,
+            key::XKB_KEY_minus => Key::Minus,
+            key::XKB_KEY_period => Key::Period,
+            key::XKB_KEY_semicolon => Key::Semicolon,
+            key::XKB_KEY_slash => Key::Slash,
+            key::XKB_KEY_space => Key::Space,
+
+            key::XKB_KEY_F1 => Key::F1,
+            key::XKB_KEY_F2 => Key::F2,
+            key::XKB_KEY_F3 => Key::F3,
+            key::XKB_KEY_F4 => Key::F4,
+            key::XKB_KEY_F5 => Key::F5,
+            key::XKB_KEY_F6 => Key::F6,
+            key::XKB_KEY_F7 => Key::F7,
+            key::XKB_KEY_F8 => Key::F8,
+            key::XKB_KEY_F9 => Key::F9,
+            key::XKB_KEY_F10 => Key::F10,
+            key::XKB_KEY_F11 => Key::F11,
+            key::XKB_KEY_F12 => Key::F12,
+
+            key::XKB_KEY_Down => Key::Down,
+            key::XKB_KEY_Left => Key::Left,
+            key::XKB_KEY_Right => Key::Right,
+            key::XKB_KEY_Up => Key::Up,
+            key::XKB_KEY_Escape => Key::Escape,
+            key::XKB_KEY_BackSpace => Key::Backspace,
+            key::XKB_KEY_Delete => Key::Delete,
+            key::XKB_KEY_End => Key::End,
+            key::XKB_KEY_Return => Key::Enter,
+            key::XKB_KEY_Home => Key::Home,
+            key::XKB_KEY_Insert => Key::Insert,
+            key::XKB_KEY_Menu => Key::Menu,
+            key::XKB_KEY_Page_Down => Key::PageDown,
+            key::XKB_KEY_Page_Up => Key::PageUp,
+            key::XKB_KEY_Pause => Key::Pause,
+            key::XKB_KEY_Tab => Key::Tab,
+            key::XKB_KEY_Num_Lock => Key::NumLock,
+            key::XKB_KEY_Caps_Lock => Key::CapsLock,
+            key::XKB_KEY_Scroll_Lock => Key::ScrollLock,
+            key::XKB_KEY_Shift_L => Key::LeftShift,
+            key::XKB_KEY_Shift_R => Key::RightShift,
+            key::XKB_KEY_Alt_L => Key::LeftAlt,
+            key::XKB_KEY_Alt_R => Key::RightAlt,
+            key::XKB_KEY_Control_L => Key::LeftCtrl,
+            key::XKB_KEY_Control_R => Key::RightCtrl,
+            key::XKB_KEY_Super_L => Key::LeftSuper,
+            key::XKB_KEY_Super_R => Key::RightSuper,
+
+            key::XKB_KEY_KP_Insert => Key::NumPad0,
+            key::XKB_KEY_KP_End => Key::NumPad1,
+            key::XKB_KEY_KP_Down => Key::NumPad2,
+            key::XKB_KEY_KP_Next => Key::NumPad3,
+            key::XKB_KEY_KP_Left => Key::NumPad4,
+            key::XKB_KEY_KP_Begin => Key::NumPad5,
+            key::XKB_KEY_KP_Right => Key::NumPad6,
+            key::XKB_KEY_KP_Home => Key::NumPad7,
+            key::XKB_KEY_KP_Up => Key::NumPad8,
+            key::XKB_KEY_KP_Prior => Key::NumPad9,
+            key::XKB_KEY_KP_Decimal => Key::NumPadDot,
+            key::XKB_KEY_KP_Divide => Key::NumPadSlash,
+            key::XKB_KEY_KP_Multiply => Key::NumPadAsterisk,
+            key::XKB_KEY_KP_Subtract => Key::NumPadMinus,
+            key::XKB_KEY_KP_Add => Key::NumPadPlus,
+            key::XKB_KEY_KP_Enter => Key::NumPadEnter,
+
+            _ => Key::Unknown,
+        };
+
+        let keycode = scancode + KEY_XKB_OFFSET;
+        let scancode = Scancode(scancode);
+
+        if !repeat {
+            if key_i != Key::Unknown {
+                key_handler.set_key_state(key_i, is_down);
+            }
+
+            key_handler.set_scancode_state(scancode, is_down);
+
+            if !is_down {
+                key_handler.stop_repeat(scancode);
+            } else if unsafe { ffi_dispatch!(XKBH, xkb_keymap_key_repeats, keymap, keycode) } != 0 {
+                key_handler.start_repeat(key_i, scancode, events.time_ms());
+            }
+        }
+
+        events.push(if is_down {
+            Event::KeyDown {
+                key: key_i,
+                scancode,
+                modifiers: key_handler.get_modifiers(),
+                repeat,
+            }
+        } else {
+            Event::KeyUp {
+                key: key_i,
+                scancode,
+                modifiers: key_handler.get_modifiers(),
+            }
+        });
+    }
+
+    fn get_xkb_modifiers(xkb_state: *mut xkb_ffi::xkb_state) -> Modifiers {
+        let names = [
+            (xkb_ffi::XKB_MOD_NAME_SHIFT, Modifiers::SHIFT),
+            (xkb_ffi::XKB_MOD_NAME_CTRL, Modifiers::CTRL),
+            (xkb_ffi::XKB_MOD_NAME_ALT, Modifiers::ALT),
+            (xkb_ffi::XKB_MOD_NAME_LOGO, Modifiers::SUPER),
+            (xkb_ffi::XKB_MOD_NAME_CAPS, Modifiers::CAPS_LOCK),
+            (xkb_ffi::XKB_MOD_NAME_NUM, Modifiers::NUM_LOCK),
+        ];
+
+        let mut modifiers = Modifiers::empty();
+
+        for (name, modifier) in names {
+            let active = unsafe {
+                ffi_dispatch!(
+                    XKBH,
+                    xkb_state_mod_name_is_active,
+                    xkb_state,
+                    name.as_ptr() as *const _,
+                    xkb_ffi::XKB_STATE_MODS_EFFECTIVE
+                )
             };
 
-            key_handler.set_key_state(key_i, is_down);
+            if active > 0 {
+                modifiers |= modifier;
+            }
         }
+
+        modifiers
     }
 
     fn handle_keymap(
@@ -1158,27 +2690,191 @@ impl Window {
         }
     }
 
+    pub fn set_cursor_grab(&mut self, grab: CursorGrab) -> Result<()> {
+        if let Some(warp) = self.pointer_warp.take() {
+            warp.locked.destroy();
+        }
+
+        match self.pointer_constraint.take() {
+            Some(PointerConstraint::Confined(confined)) => confined.destroy(),
+            Some(PointerConstraint::Locked(locked)) => locked.destroy(),
+            None => {}
+        }
+
+        if grab == CursorGrab::None {
+            return Ok(());
+        }
+
+        let (constraints, pointer) = self.input.get_pointer_constraints().ok_or_else(|| {
+            Error::NotSupported("the compositor doesn't support pointer constraints".to_owned())
+        })?;
+        let surface = &self.display.surface;
+
+        // persistent constraints are reactivated when the window gets focus again
+        let constraint = if grab == CursorGrab::Locked {
+            let locked = constraints.lock_pointer(surface, pointer, None, Lifetime::Persistent);
+            locked.quick_assign(|_, _, _| {});
+            PointerConstraint::Locked(locked)
+        } else {
+            let confined =
+                constraints.confine_pointer(surface, pointer, None, Lifetime::Persistent);
+            confined.quick_assign(|_, _, _| {});
+            PointerConstraint::Confined(confined)
+        };
+
+        self.pointer_constraint = Some(constraint);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn get_mouse_delta(&self) -> (f32, f32) {
+        self.mouse_delta
+    }
+
+    #[inline]
+    pub fn get_touches(&self) -> &[Touch] {
+        &self.touches
+    }
+
     #[inline]
-    fn decode_cursor(cursor: CursorStyle) -> &'static str {
-        match cursor {
-            CursorStyle::Arrow => "arrow",
-            CursorStyle::Ibeam => "xterm",
-            CursorStyle::Crosshair => "crosshair",
-            CursorStyle::ClosedHand => "hand2",
-            CursorStyle::OpenHand => "hand2",
-            CursorStyle::ResizeLeftRight => "sb_h_double_arrow",
-            CursorStyle::ResizeUpDown => "sb_v_double_arrow",
-            CursorStyle::ResizeAll => "diamond_cross",
+    pub fn get_click_count(&self, button: MouseButton) -> u32 {
+        self.clicks.get(button)
+    }
+
+    #[inline]
+    pub fn set_default_double_click_interval(&mut self, interval: Duration) {
+        self.clicks.default_interval = u32::try_from(interval.as_millis()).unwrap_or(u32::MAX);
+    }
+
+    #[cfg(all(feature = "gamepad", target_os = "linux"))]
+    #[inline]
+    pub fn get_gamepads(&self) -> &[crate::Gamepad] {
+        self.gamepads.get_gamepads()
+    }
+
+    #[inline]
+    pub fn get_pen_state(&self) -> Option<PenState> {
+        self.pen_frame
+    }
+
+    pub fn set_mouse_pos(&mut self, x: f32, y: f32) -> Result<()> {
+        if !self.pointer_focus {
+            return Err(Error::NotSupported(
+                "the window doesn't have the pointer focus".to_owned(),
+            ));
+        }
+
+        let scale = self.scale as f32;
+        let (x, y) = (x * scale, y * scale);
+
+        // Clients can't move the cursor, but the compositor moves it to the position hint of a
+        // locked pointer when it's unlocked. Lock it temporarily if it isn't already.
+        if let Some(PointerConstraint::Locked(locked)) = &self.pointer_constraint {
+            locked.set_cursor_position_hint(x as f64, y as f64);
+            self.display.surface.commit();
+            self.mouse_x = x;
+            self.mouse_y = y;
+            return Ok(());
+        }
+
+        // a move that is still waiting for its lock only needs a new position
+        if let Some(warp) = &mut self.pointer_warp {
+            warp.locked.set_cursor_position_hint(x as f64, y as f64);
+            warp.position = (x, y);
+            self.display.surface.commit();
+            return Ok(());
+        }
+
+        // only one constraint can be active at a time
+        let confined = self.pointer_constraint.is_some();
+        self.set_cursor_grab(CursorGrab::None)?;
+
+        let (constraints, pointer) = self.input.get_pointer_constraints().ok_or_else(|| {
+            Error::NotSupported("the compositor doesn't support pointer constraints".to_owned())
+        })?;
+
+        let locked =
+            constraints.lock_pointer(&self.display.surface, pointer, None, Lifetime::Oneshot);
+
+        let state = Rc::new(RefCell::new(None));
+        let state_clone = state.clone();
+        locked.quick_assign(move |_, event, _| {
+            use wayland_protocols::unstable::pointer_constraints::v1::client::zwp_locked_pointer_v1::Event;
+
+            match event {
+                Event::Locked => *state_clone.borrow_mut() = Some(true),
+                Event::Unlocked => {
+                    state_clone.borrow_mut().get_or_insert(false);
+                }
+                _ => {}
+            }
+        });
+        locked.set_cursor_position_hint(x as f64, y as f64);
+        self.display.surface.commit();
+
+        // the lock is destroyed in `update` once the compositor has activated it
+        self.pointer_warp = Some(PointerWarp {
+            locked,
+            state,
+            position: (x, y),
+            confined,
+        });
+        Ok(())
+    }
+
+    /// Finishes a cursor move started by `set_mouse_pos` when the compositor has answered
+    fn process_pointer_warp(&mut self) {
+        let state = match &self.pointer_warp {
+            Some(warp) => *warp.state.borrow(),
+            None => return,
+        };
+        let moved = match state {
+            Some(moved) => moved,
+            None => return,
+        };
+
+        let warp = self.pointer_warp.take().unwrap();
+        warp.locked.destroy();
+
+        if moved {
+            self.mouse_x = warp.position.0;
+            self.mouse_y = warp.position.1;
+        }
+
+        if warp.confined {
+            let _ = self.set_cursor_grab(CursorGrab::Confined);
         }
     }
 
     #[inline]
     pub fn set_cursor_style(&mut self, cursor: CursorStyle) {
-        if self.prev_cursor != cursor {
-            self.display
-                .update_cursor(Self::decode_cursor(cursor))
-                .unwrap();
+        if self.prev_cursor != cursor || self.custom_cursor {
+            self.display.update_cursor(cursor).unwrap();
             self.prev_cursor = cursor;
+            self.custom_cursor = false;
+            // the hotspot is set together with the cursor surface
+            self.set_pointer_cursor(self.pointer_enter_serial);
+        }
+    }
+
+    pub fn set_custom_cursor(&mut self, cursor: &CustomCursor) {
+        if self.display.update_custom_cursor(cursor).is_ok() {
+            self.custom_cursor = true;
+            // the hotspot is set together with the cursor surface
+            self.set_pointer_cursor(self.pointer_enter_serial);
+        }
+    }
+
+    /// Shows (or hides) the cursor surface, `serial` has to be the serial of the latest pointer
+    /// enter (or other pointer event)
+    fn set_pointer_cursor(&self, serial: u32) {
+        if self.pointer_visibility {
+            let (x, y) = self.display.cursor_hotspot;
+            self.input
+                .get_pointer()
+                .set_cursor(serial, Some(&self.display.cursor_surface), x, y);
+        } else {
+            self.input.get_pointer().set_cursor(serial, None, 0, 0);
         }
     }
 
@@ -1,30 +1,42 @@
 use super::common::{
-    image_center, image_resize_linear, image_resize_linear_aspect_fill, image_upper_left, Menu,
+    image_center, image_resize_linear, image_resize_linear_aspect_fill, image_upper_left,
+    parse_uri_list, premultiply_alpha, ClickCounter, ClipboardData, Menu, Selection, CURSOR_NAMES,
+    PIXELS_PER_NOTCH, URI_LIST_MIME_TYPE,
 };
+#[cfg(all(feature = "gamepad", target_os = "linux"))]
+use super::gamepad::Gamepads;
 use crate::{
-    check_buffer_size, error::Error, icon::Icon, key_handler::KeyHandler, rate::UpdateRate,
-    CursorStyle, InputCallback, Key, KeyRepeat, MenuHandle, MouseButton, MouseMode, Result, Scale,
-    ScaleMode, UnixMenu, WindowOptions,
+    check_buffer_size, error::Error, event::EventBuffer, icon::Icon, key_handler::KeyHandler,
+    rate::UpdateRate, CursorGrab, CursorStyle, CustomCursor, Event, Fullscreen, InputCallback, Key,
+    KeyRepeat, MenuHandle, Modifiers, Monitor, MouseButton, MouseMode, PenState, Result, Scale,
+    ScaleMode, Scancode, ScrollSource, TimedEvent, Touch, TouchPhase, UnixMenu, VideoMode,
+    WindowOptions, WindowSize,
 };
 use raw_window_handle::{
     DisplayHandle, HandleError, HasDisplayHandle, HasWindowHandle, RawDisplayHandle,
     RawWindowHandle, WindowHandle, XlibDisplayHandle, XlibWindowHandle,
 };
 use std::{
-    convert::TryFrom,
-    ffi::{c_char, c_int, c_long, c_uchar, c_uint, c_ulong, c_void, CStr, CString},
+    convert::{TryFrom, TryInto},
+    ffi::{
+        c_char, c_int, c_long, c_short, c_uchar, c_uint, c_ulong, c_ushort, c_void, CStr, CString,
+    },
     mem::MaybeUninit,
     ptr::NonNull,
     time::Duration,
 };
 use x11_dl::{
     keysym::*,
-    xcursor,
+    xcursor, xinput2,
     xlib::{
-        self, KeyPressMask, KeyReleaseMask, KeySym, Status, XEvent, XIMPreeditNothing,
-        XIMStatusNothing, XKeyEvent, XNClientWindow_0, XNFocusWindow_0, XNInputStyle_0,
+        self, KeyPressMask, KeyReleaseMask, KeySym, Status, XEvent, XIMCaretDirection,
+        XIMPreeditCallbacks, XIMPreeditNothing, XIMPreeditPosition, XIMStatusNothing, XKeyEvent,
+        XNClientWindow_0, XNFocusWindow_0, XNInputStyle_0, XNPreeditAttributes_0,
+        XNPreeditCaretCallback_0, XNPreeditDoneCallback_0, XNPreeditDrawCallback_0,
+        XNPreeditStartCallback_0, XNQueryInputStyle_0, XNSpotLocation_0, XPointer,
         XWindowAttributes, XrmDatabase, XIC, XIM,
     },
+    xrandr,
 };
 
 // NOTE: the x11-dl crate does not define Button6, Button7, Button8, and Button9
@@ -33,6 +45,18 @@ const Button7: c_uint = xlib::Button5 + 2;
 const Button8: c_uint = xlib::Button5 + 3;
 const Button9: c_uint = xlib::Button5 + 4;
 
+// How long to wait for other clients when reading a selection (clipboard)
+const SELECTION_TIMEOUT: Duration = Duration::from_secs(1);
+
+// NOTE: the x11-dl crate does not define XIMStyle and XIMStyles
+type XIMStyle = c_ulong;
+
+#[repr(C)]
+struct XIMStyles {
+    count_styles: c_ushort,
+    supported_styles: *mut XIMStyle,
+}
+
 #[repr(C)]
 struct MwmHints {
     flags: c_ulong,
@@ -42,6 +66,62 @@ struct MwmHints {
     status: c_ulong,
 }
 
+/// Atoms of the XDND (drag and drop) protocol
+#[derive(Clone, Copy, Default)]
+struct XdndAtoms {
+    aware: xlib::Atom,
+    enter: xlib::Atom,
+    position: xlib::Atom,
+    status: xlib::Atom,
+    leave: xlib::Atom,
+    drop: xlib::Atom,
+    finished: xlib::Atom,
+    selection: xlib::Atom,
+    type_list: xlib::Atom,
+    action_copy: xlib::Atom,
+    uri_list: xlib::Atom,
+}
+
+// The XDND protocol version we support
+const XDND_VERSION: c_long = 5;
+
+/// Atoms of the window manager hints (EWMH) used to change the state of the window
+#[derive(Clone, Copy, Default)]
+struct NetWmAtoms {
+    supported: xlib::Atom,
+    state: xlib::Atom,
+    state_fullscreen: xlib::Atom,
+    state_above: xlib::Atom,
+    state_maximized_vert: xlib::Atom,
+    state_maximized_horz: xlib::Atom,
+    state_hidden: xlib::Atom,
+}
+
+/// An active CRTC (the part of the graphics card driving a monitor) as reported by XRandR
+#[derive(Clone)]
+struct Crtc {
+    id: xrandr::RRCrtc,
+    x: c_int,
+    y: c_int,
+    width: c_uint,
+    height: c_uint,
+    mode: xrandr::RRMode,
+    rotation: xrandr::Rotation,
+    outputs: Vec<xrandr::RROutput>,
+    // name of the first output and refresh rate of the mode, for `Monitor`
+    name: String,
+    refresh_rate: u32,
+}
+
+impl Crtc {
+    fn contains(&self, x: c_int, y: c_int) -> bool {
+        x >= self.x
+            && x < self.x + self.width as c_int
+            && y >= self.y
+            && y < self.y + self.height as c_int
+    }
+}
+
 struct DisplayInfo {
     lib: x11_dl::xlib::Xlib,
     display: *mut xlib::Display,
@@ -53,9 +133,24 @@ struct DisplayInfo {
     screen_height: usize,
     _context: xlib::XContext,
     cursor_lib: x11_dl::xcursor::Xcursor,
-    cursors: [xlib::Cursor; 8],
+    cursors: [xlib::Cursor; CURSOR_NAMES.len()],
     keyb_ext: bool,
+    // XInput2 is used for raw (relative) mouse motion, see `get_mouse_delta`, and touch events
+    xinput2: Option<xinput2::XInput2>,
+    xinput2_opcode: c_int,
+    // touch events requires XInput 2.2
+    xinput2_touch: bool,
+    // valuator labels of the pressure and tilt axes of tablet pens
+    pen_axes: [xlib::Atom; 3],
+    // XRandR is used to find the monitors and to change the video mode, see `Fullscreen`
+    xrandr: Option<xrandr::Xrandr>,
     wm_delete_window: xlib::Atom,
+    clipboard: xlib::Atom,
+    targets: xlib::Atom,
+    incr: xlib::Atom,
+    selection_property: xlib::Atom,
+    xdnd: XdndAtoms,
+    net_wm: NetWmAtoms,
 }
 
 impl DisplayInfo {
@@ -64,6 +159,8 @@ impl DisplayInfo {
 
         display.check_formats()?;
         display.check_extensions()?;
+        display.init_xinput2();
+        display.init_xrandr();
         display.init_cursors();
         display.init_atoms();
 
@@ -138,9 +235,20 @@ impl DisplayInfo {
                 _context: context,
                 cursor_lib,
                 // the following are determined later...
-                cursors: [0; 8],
+                cursors: [0; CURSOR_NAMES.len()],
                 keyb_ext: false,
+                xinput2: None,
+                xinput2_opcode: 0,
+                xinput2_touch: false,
+                pen_axes: [0; 3],
+                xrandr: None,
                 wm_delete_window: 0,
+                clipboard: 0,
+                targets: 0,
+                incr: 0,
+                selection_property: 0,
+                xdnd: XdndAtoms::default(),
+                net_wm: NetWmAtoms::default(),
             })
         }
     }
@@ -200,26 +308,364 @@ impl DisplayInfo {
         Ok(())
     }
 
-    fn init_cursors(&mut self) {
-        self.cursors[0] = self.load_cursor(b"arrow\0");
-        self.cursors[1] = self.load_cursor(b"xterm\0");
-        self.cursors[2] = self.load_cursor(b"crosshair\0");
-        self.cursors[3] = self.load_cursor(b"hand2\0");
-        self.cursors[4] = self.load_cursor(b"hand2\0");
-        self.cursors[5] = self.load_cursor(b"sb_h_double_arrow\0");
-        self.cursors[6] = self.load_cursor(b"sb_v_double_arrow\0");
-        self.cursors[7] = self.load_cursor(b"diamond_cross\0");
+    fn init_xinput2(&mut self) {
+        let xinput2 = match xinput2::XInput2::open() {
+            Ok(xinput2) => xinput2,
+            Err(_) => return,
+        };
+
+        unsafe {
+            let mut opcode = 0;
+            let mut event = 0;
+            let mut error = 0;
+            let name = CStr::from_bytes_with_nul_unchecked(b"XInputExtension\0");
+
+            if (self.lib.XQueryExtension)(
+                self.display,
+                name.as_ptr(),
+                &mut opcode,
+                &mut event,
+                &mut error,
+            ) == xlib::False
+            {
+                return;
+            }
+
+            // raw events requires version 2.0 and touch events 2.2. The server replies with the
+            // highest version it supports up to the requested one
+            let mut major = 2;
+            let mut minor = 2;
+            if (xinput2.XIQueryVersion)(self.display, &mut major, &mut minor)
+                != xlib::Success as c_int
+            {
+                return;
+            }
+
+            // raw events are only delivered to the root window, hierarchy changes are used to
+            // find tablet pens that are plugged in
+            let mut raw_mask = [0u8; 4];
+            xinput2::XISetMask(&mut raw_mask, xinput2::XI_RawMotion);
+            let mut hierarchy_mask = [0u8; 4];
+            xinput2::XISetMask(&mut hierarchy_mask, xinput2::XI_HierarchyChanged);
+            let mut event_masks = [
+                xinput2::XIEventMask {
+                    deviceid: xinput2::XIAllMasterDevices,
+                    mask_len: raw_mask.len() as c_int,
+                    mask: raw_mask.as_mut_ptr(),
+                },
+                xinput2::XIEventMask {
+                    deviceid: xinput2::XIAllDevices,
+                    mask_len: hierarchy_mask.len() as c_int,
+                    mask: hierarchy_mask.as_mut_ptr(),
+                },
+            ];
+            (xinput2.XISelectEvents)(
+                self.display,
+                (self.lib.XDefaultRootWindow)(self.display),
+                event_masks.as_mut_ptr(),
+                event_masks.len() as c_int,
+            );
+
+            self.xinput2_opcode = opcode;
+            self.xinput2_touch = major > 2 || minor >= 2;
+        }
+
+        // the labels are only present if some device has these axes
+        self.pen_axes = [
+            self.intern_atom(b"Abs Pressure\0", true),
+            self.intern_atom(b"Abs Tilt X\0", true),
+            self.intern_atom(b"Abs Tilt Y\0", true),
+        ];
+
+        self.xinput2 = Some(xinput2);
     }
 
-    fn load_cursor(&mut self, name: &'static [u8]) -> xlib::Cursor {
+    fn init_xrandr(&mut self) {
+        let xrandr = match xrandr::Xrandr::open() {
+            Ok(xrandr) => xrandr,
+            Err(_) => return,
+        };
+
         unsafe {
-            let name = CStr::from_bytes_with_nul_unchecked(name);
-            (self.cursor_lib.XcursorLibraryLoadCursor)(self.display, name.as_ptr())
+            let mut event = 0;
+            let mut error = 0;
+            if (xrandr.XRRQueryExtension)(self.display, &mut event, &mut error) == xlib::False {
+                return;
+            }
+
+            // XRRGetScreenResourcesCurrent requires version 1.3
+            let mut major = 0;
+            let mut minor = 0;
+            if (xrandr.XRRQueryVersion)(self.display, &mut major, &mut minor) == 0
+                || (major, minor) < (1, 3)
+            {
+                return;
+            }
+        }
+
+        self.xrandr = Some(xrandr);
+    }
+
+    /// Returns the active CRTCs, which is empty if XRandR isn't available
+    unsafe fn crtcs(&self) -> Vec<Crtc> {
+        let xrandr = match &self.xrandr {
+            Some(xrandr) => xrandr,
+            None => return Vec::new(),
+        };
+
+        let root = (self.lib.XDefaultRootWindow)(self.display);
+        let resources = (xrandr.XRRGetScreenResourcesCurrent)(self.display, root);
+        if resources.is_null() {
+            return Vec::new();
+        }
+
+        let mut crtcs = Vec::new();
+
+        for &id in xrandr_slice((*resources).crtcs, (*resources).ncrtc) {
+            let info = (xrandr.XRRGetCrtcInfo)(self.display, resources, id);
+            if info.is_null() {
+                continue;
+            }
+
+            // disabled CRTCs have no mode
+            if (*info).mode != 0 {
+                let outputs = xrandr_slice((*info).outputs, (*info).noutput).to_vec();
+                let refresh_rate = xrandr_slice((*resources).modes, (*resources).nmode)
+                    .iter()
+                    .find(|mode| mode.id == (*info).mode)
+                    .map_or(0, mode_refresh_rate);
+
+                let mut name = String::new();
+                if let Some(&output) = outputs.first() {
+                    let output_info = (xrandr.XRRGetOutputInfo)(self.display, resources, output);
+                    if !output_info.is_null() {
+                        let bytes =
+                            xrandr_slice((*output_info).name as *const u8, (*output_info).nameLen);
+                        name = String::from_utf8_lossy(bytes).into_owned();
+                        (xrandr.XRRFreeOutputInfo)(output_info);
+                    }
+                }
+
+                crtcs.push(Crtc {
+                    id,
+                    x: (*info).x,
+                    y: (*info).y,
+                    width: (*info).width,
+                    height: (*info).height,
+                    mode: (*info).mode,
+                    rotation: (*info).rotation,
+                    outputs,
+                    name,
+                    refresh_rate,
+                });
+            }
+
+            (xrandr.XRRFreeCrtcInfo)(info);
+        }
+
+        (xrandr.XRRFreeScreenResources)(resources);
+        crtcs
+    }
+
+    /// Returns the monitors, which is the whole screen if XRandR isn't available
+    unsafe fn monitors(&self) -> Vec<(Monitor, Option<Crtc>)> {
+        // X11 has no scale per monitor, desktops set Xft.dpi for the whole screen
+        let scale_factor = self.xft_dpi().map_or(1.0, |dpi| dpi / 96.0);
+
+        let monitors: Vec<_> = self
+            .crtcs()
+            .into_iter()
+            .map(|crtc| {
+                let monitor = Monitor {
+                    name: crtc.name.clone(),
+                    position: (crtc.x, crtc.y),
+                    size: (crtc.width as usize, crtc.height as usize),
+                    refresh_rate: crtc.refresh_rate,
+                    scale_factor,
+                };
+                (monitor, Some(crtc))
+            })
+            .collect();
+
+        if !monitors.is_empty() {
+            return monitors;
+        }
+
+        let screen = Monitor {
+            name: String::new(),
+            position: (0, 0),
+            size: (self.screen_width, self.screen_height),
+            refresh_rate: 0,
+            scale_factor,
+        };
+        vec![(screen, None)]
+    }
+
+    /// Returns the monitor with the mouse pointer, or the first one
+    unsafe fn pointer_monitor(&self) -> Monitor {
+        let root = (self.lib.XDefaultRootWindow)(self.display);
+        let (mut root_return, mut child) = (0, 0);
+        let (mut x, mut y, mut window_x, mut window_y) = (0, 0, 0, 0);
+        let mut mask = 0;
+        (self.lib.XQueryPointer)(
+            self.display,
+            root,
+            &mut root_return,
+            &mut child,
+            &mut x,
+            &mut y,
+            &mut window_x,
+            &mut window_y,
+            &mut mask,
+        );
+
+        let mut monitors = self.monitors();
+        let index = monitors
+            .iter()
+            .position(|(_, crtc)| crtc.as_ref().is_some_and(|crtc| crtc.contains(x, y)))
+            .unwrap_or(0);
+        monitors.swap_remove(index).0
+    }
+
+    /// Reads the Xft.dpi resource, which desktops set to scale things up on HiDPI monitors
+    unsafe fn xft_dpi(&self) -> Option<f32> {
+        let resources = (self.lib.XResourceManagerString)(self.display);
+        if resources.is_null() {
+            return None;
+        }
+
+        CStr::from_ptr(resources)
+            .to_string_lossy()
+            .lines()
+            .find_map(|line| line.strip_prefix("Xft.dpi:"))
+            .and_then(|dpi| dpi.trim().parse().ok())
+    }
+
+    /// Finds the mode of the monitor driven by `crtc` that matches the size of `video_mode` and
+    /// has the closest refresh rate (or the highest if the refresh rate is 0)
+    unsafe fn find_video_mode(&self, crtc: &Crtc, video_mode: VideoMode) -> Option<xrandr::RRMode> {
+        let xrandr = self.xrandr.as_ref()?;
+        let output = *crtc.outputs.first()?;
+
+        let root = (self.lib.XDefaultRootWindow)(self.display);
+        let resources = (xrandr.XRRGetScreenResourcesCurrent)(self.display, root);
+        if resources.is_null() {
+            return None;
+        }
+
+        let mut found = None;
+        let info = (xrandr.XRRGetOutputInfo)(self.display, resources, output);
+
+        if !info.is_null() {
+            let output_modes = xrandr_slice((*info).modes, (*info).nmode);
+
+            found = xrandr_slice((*resources).modes, (*resources).nmode)
+                .iter()
+                .filter(|mode| {
+                    output_modes.contains(&mode.id)
+                        && mode.width == video_mode.width
+                        && mode.height == video_mode.height
+                })
+                .min_by_key(|mode| {
+                    let refresh_rate = mode_refresh_rate(mode);
+                    if video_mode.refresh_rate == 0 {
+                        u32::MAX - refresh_rate
+                    } else {
+                        refresh_rate.abs_diff(video_mode.refresh_rate)
+                    }
+                })
+                .map(|mode| mode.id);
+
+            (xrandr.XRRFreeOutputInfo)(info);
+        }
+
+        (xrandr.XRRFreeScreenResources)(resources);
+        found
+    }
+
+    /// Changes the mode of a CRTC, keeping its position, rotation and outputs
+    unsafe fn set_crtc_mode(&self, crtc: &Crtc, mode: xrandr::RRMode) -> bool {
+        let xrandr = match &self.xrandr {
+            Some(xrandr) => xrandr,
+            None => return false,
+        };
+
+        let root = (self.lib.XDefaultRootWindow)(self.display);
+        let resources = (xrandr.XRRGetScreenResourcesCurrent)(self.display, root);
+        if resources.is_null() {
+            return false;
+        }
+
+        let mut outputs = crtc.outputs.clone();
+        let status = (xrandr.XRRSetCrtcConfig)(
+            self.display,
+            resources,
+            crtc.id,
+            (*resources).timestamp,
+            crtc.x,
+            crtc.y,
+            mode,
+            crtc.rotation,
+            outputs.as_mut_ptr(),
+            outputs.len() as c_int,
+        );
+
+        (xrandr.XRRFreeScreenResources)(resources);
+        status == xlib::Success as Status
+    }
+
+    fn init_cursors(&mut self) {
+        for (index, names) in CURSOR_NAMES.iter().enumerate() {
+            // use the first name found in the theme
+            self.cursors[index] = names
+                .iter()
+                .map(|name| self.load_cursor(name))
+                .find(|&cursor| cursor != 0)
+                .unwrap_or(self.cursors[0]);
         }
     }
 
+    fn load_cursor(&self, name: &str) -> xlib::Cursor {
+        let name = CString::new(name).unwrap();
+        unsafe { (self.cursor_lib.XcursorLibraryLoadCursor)(self.display, name.as_ptr()) }
+    }
+
     fn init_atoms(&mut self) {
         self.wm_delete_window = self.intern_atom(b"WM_DELETE_WINDOW\0", false);
+        self.clipboard = self.intern_atom(b"CLIPBOARD\0", false);
+        self.targets = self.intern_atom(b"TARGETS\0", false);
+        self.incr = self.intern_atom(b"INCR\0", false);
+        // property on our window where the selection owners put the data we ask for
+        self.selection_property = self.intern_atom(b"MINIFB_SELECTION\0", false);
+
+        self.net_wm = NetWmAtoms {
+            supported: self.intern_atom(b"_NET_SUPPORTED\0", false),
+            state: self.intern_atom(b"_NET_WM_STATE\0", false),
+            state_fullscreen: self.intern_atom(b"_NET_WM_STATE_FULLSCREEN\0", false),
+            state_above: self.intern_atom(b"_NET_WM_STATE_ABOVE\0", false),
+            state_maximized_vert: self.intern_atom(b"_NET_WM_STATE_MAXIMIZED_VERT\0", false),
+            state_maximized_horz: self.intern_atom(b"_NET_WM_STATE_MAXIMIZED_HORZ\0", false),
+            state_hidden: self.intern_atom(b"_NET_WM_STATE_HIDDEN\0", false),
+        };
+
+        self.xdnd = XdndAtoms {
+            aware: self.intern_atom(b"XdndAware\0", false),
+            enter: self.intern_atom(b"XdndEnter\0", false),
+            position: self.intern_atom(b"XdndPosition\0", false),
+            status: self.intern_atom(b"XdndStatus\0", false),
+            leave: self.intern_atom(b"XdndLeave\0", false),
+            drop: self.intern_atom(b"XdndDrop\0", false),
+            finished: self.intern_atom(b"XdndFinished\0", false),
+            selection: self.intern_atom(b"XdndSelection\0", false),
+            type_list: self.intern_atom(b"XdndTypeList\0", false),
+            action_copy: self.intern_atom(b"XdndActionCopy\0", false),
+            uri_list: self.mime_type_atom(URI_LIST_MIME_TYPE),
+        };
+    }
+
+    fn mime_type_atom(&mut self, mime_type: &str) -> xlib::Atom {
+        let name = CString::new(mime_type).unwrap();
+        unsafe { (self.lib.XInternAtom)(self.display, name.as_ptr(), xlib::False) }
     }
 
     fn intern_atom(&mut self, name: &'static [u8], only_if_exists: bool) -> xlib::Atom {
@@ -236,6 +682,14 @@ impl DisplayInfo {
             )
         }
     }
+
+    /// Largest property (in bytes) that fits in a request without the BIG-REQUESTS extension,
+    /// larger selections are sent in chunks of this size
+    fn max_property_size(&self) -> usize {
+        // the request size is in 4 byte units and ChangeProperty has a 24 byte header
+        let max_request_size = unsafe { (self.lib.XMaxRequestSize)(self.display) } as usize;
+        (max_request_size * 4).saturating_sub(24)
+    }
 }
 
 impl Drop for DisplayInfo {
@@ -247,6 +701,142 @@ impl Drop for DisplayInfo {
     }
 }
 
+/// The text being composed by the input method. This is updated by the XIM preedit callbacks
+/// (on-the-spot input style) and is boxed as its address is passed to them as client data.
+#[derive(Default)]
+struct ImePreedit {
+    text: Vec<char>,
+    caret: usize,
+    events: Vec<Event>,
+}
+
+impl ImePreedit {
+    fn push_event(&mut self) {
+        let cursor = self.text[..self.caret].iter().map(|c| c.len_utf8()).sum();
+
+        self.events.push(Event::Preedit {
+            text: self.text.iter().collect(),
+            cursor: Some((cursor, cursor)),
+        });
+    }
+}
+
+unsafe extern "C" fn preedit_start_callback(
+    _xic: XIC,
+    _client_data: XPointer,
+    _call_data: XPointer,
+) -> c_int {
+    // no limit on the length of the preedit text
+    -1
+}
+
+unsafe extern "C" fn preedit_done_callback(_xim: XIM, client_data: XPointer, _call_data: XPointer) {
+    let preedit = &mut *(client_data as *mut ImePreedit);
+
+    preedit.text.clear();
+    preedit.caret = 0;
+    preedit.events.push(Event::Preedit {
+        text: String::new(),
+        cursor: None,
+    });
+}
+
+unsafe extern "C" fn preedit_draw_callback(_xim: XIM, client_data: XPointer, call_data: XPointer) {
+    let preedit = &mut *(client_data as *mut ImePreedit);
+    let data = &*(call_data as *const xlib::XIMPreeditDrawCallbackStruct);
+
+    let len = preedit.text.len();
+    let first = (data.chg_first.max(0) as usize).min(len);
+    let last = (first + data.chg_length.max(0) as usize).min(len);
+
+    // a null text means that the changed range was deleted
+    let mut text = Vec::new();
+    if !data.text.is_null() {
+        let xim_text = &*data.text;
+        // only multi byte strings are used with the UTF-8 locales we care about
+        if xim_text.encoding_is_wchar == 0 && !xim_text.string.multi_byte.is_null() {
+            text = CStr::from_ptr(xim_text.string.multi_byte)
+                .to_string_lossy()
+                .chars()
+                .collect();
+        }
+    }
+
+    preedit.text.splice(first..last, text);
+    preedit.caret = (data.caret.max(0) as usize).min(preedit.text.len());
+    preedit.push_event();
+}
+
+unsafe extern "C" fn preedit_caret_callback(_xim: XIM, client_data: XPointer, call_data: XPointer) {
+    let preedit = &mut *(client_data as *mut ImePreedit);
+    let data = &mut *(call_data as *mut xlib::XIMPreeditCaretCallbackStruct);
+
+    let len = preedit.text.len();
+    preedit.caret = match data.direction {
+        XIMCaretDirection::XIMAbsolutePosition => data.position.max(0) as usize,
+        XIMCaretDirection::XIMForwardChar => preedit.caret + 1,
+        XIMCaretDirection::XIMBackwardChar => preedit.caret.saturating_sub(1),
+        XIMCaretDirection::XIMLineStart => 0,
+        XIMCaretDirection::XIMLineEnd => len,
+        _ => preedit.caret,
+    }
+    .min(len);
+
+    // the input method expects the new position back
+    data.position = preedit.caret as c_int;
+    preedit.push_event();
+}
+
+/// A selection that is too large for a single property, sent in chunks with the INCR protocol
+struct IncrTransfer {
+    requestor: xlib::Window,
+    property: xlib::Atom,
+    target: xlib::Atom,
+    data: Vec<u8>,
+    // how much of the data has been sent
+    offset: usize,
+}
+
+/// A drag and drop operation (XDND) over the window
+#[derive(Clone, Copy)]
+struct XdndDrag {
+    source: xlib::Window,
+    version: c_long,
+    // the source offers files (text/uri-list)
+    accepted: bool,
+    // DragEnter has been sent, which happens on the first position message
+    entered: bool,
+}
+
+/// A tablet pen (or eraser) found with XInput2, see `get_pen_state`
+#[derive(Clone, Copy)]
+struct PenDevice {
+    id: c_int,
+    eraser: bool,
+    // valuator number, min and max
+    pressure: Option<(c_int, f64, f64)>,
+    // valuator numbers
+    tilt_x: Option<c_int>,
+    tilt_y: Option<c_int>,
+}
+
+/// A pointer device with smooth scrolling, see `process_scroll_event`
+struct ScrollDevice {
+    id: c_int,
+    source: ScrollSource,
+    // horizontal and vertical
+    axes: [Option<ScrollValuator>; 2],
+}
+
+#[derive(Clone, Copy)]
+struct ScrollValuator {
+    number: c_int,
+    // distance of a wheel click
+    increment: f64,
+    // read again when the pointer enters the window, see `read_scroll_valuators`
+    last: Option<f64>,
+}
+
 #[derive(Clone, Copy, Eq, PartialEq)]
 enum ProcessEventResult {
     Ok,
@@ -259,12 +849,32 @@ pub struct Window {
     handle: xlib::Window,
     xim: XIM,
     xic: XIC,
+    ime_preedit: Box<ImePreedit>,
+    // data (by target) that we serve while we own the CLIPBOARD and PRIMARY selections
+    clipboard: Vec<(xlib::Atom, Vec<u8>)>,
+    primary: Vec<(xlib::Atom, Vec<u8>)>,
+    incr_transfers: Vec<IncrTransfer>,
+    drag: Option<XdndDrag>,
 
     ximage: *mut xlib::XImage,
     draw_buffer: Vec<u32>,
 
     width: u32,  // this is the *scaled* size
     height: u32, //
+    // the size the window is kept at when it isn't resizable
+    fixed_size: Option<(u32, u32)>,
+    // limits of a resizable window, see `update_size_hints`
+    min_size: Option<(u32, u32)>,
+    max_size: Option<(u32, u32)>,
+    aspect_ratio: Option<(u32, u32)>,
+    fullscreen: Fullscreen,
+    // the CRTC as it was before its mode was changed for exclusive fullscreen
+    saved_crtc: Option<Crtc>,
+    // the states are set with the _NET_WM_STATE property until the window is mapped
+    mapped: bool,
+    // the state as reported by the window manager
+    maximized: bool,
+    minimized: bool,
 
     scale: i32,
     bg_color: u32,
@@ -276,11 +886,37 @@ pub struct Window {
     scroll_y: f32,
     buttons: [u8; 5],
     prev_cursor: CursorStyle,
+    // cursor set with set_custom_cursor, 0 if a standard cursor is used
+    custom_cursor: xlib::Cursor,
+    cursor_visible: bool,
     active: bool,
+    cursor_grab: CursorGrab,
+    // where the pointer is kept with CursorGrab::Locked and if it has to be moved back there
+    lock_pos: (c_int, c_int),
+    lock_moved: bool,
+    mouse_delta: (f32, f32),
+    clicks: ClickCounter,
+    touches: Vec<Touch>,
+    #[cfg(all(feature = "gamepad", target_os = "linux"))]
+    gamepads: Gamepads,
+    pen_devices: Vec<PenDevice>,
+    scroll_devices: Vec<ScrollDevice>,
+    // time of the wheel presses and releases the server emulated from smooth scrolling, the
+    // core events for these are skipped as the valuators are used instead
+    emulated_wheel: Vec<xlib::Time>,
+    pen: Option<PenState>,
 
     should_close: bool, // received delete window message from X server
+    intercept_close: bool,
+    close_requested: bool,
 
     key_handler: KeyHandler,
+    // bit mask of the keycodes the server auto repeats
+    auto_repeat_keys: [u8; 32],
+    events: EventBuffer,
+    // server time of the last input or property event, which ICCCM asks to use for the
+    // selection requests instead of CurrentTime (0, used until there has been an event)
+    last_server_time: xlib::Time,
     update_rate: UpdateRate,
     menu_counter: MenuHandle,
     menus: Vec<UnixMenu>,
@@ -319,8 +955,12 @@ impl Window {
 
         let mut d = DisplayInfo::new(opts.transparency)?;
 
+        // the window is opened on the monitor with the pointer
+        let monitor = unsafe { d.pointer_monitor() };
+        let (monitor_width, monitor_height) = monitor.size;
+
         let scale =
-            Self::get_scale_factor(width, height, d.screen_width, d.screen_height, opts.scale);
+            Self::get_scale_factor(width, height, monitor_width, monitor_height, opts.scale);
 
         let width = width * scale;
         let height = height * scale;
@@ -339,22 +979,24 @@ impl Window {
 
             attributes.backing_store = xlib::NotUseful;
 
-            let x = if d.screen_width > width {
-                (d.screen_width - width) / 2
+            let x = if monitor_width > width {
+                (monitor_width - width) / 2
             } else {
                 0
-            };
-            let y = if d.screen_height > height {
-                (d.screen_height - height) / 2
+            } as i32
+                + monitor.position.0;
+            let y = if monitor_height > height {
+                (monitor_height - height) / 2
             } else {
                 0
-            };
+            } as i32
+                + monitor.position.1;
 
             let handle = (d.lib.XCreateWindow)(
                 d.display,
                 root,
-                x as i32,
-                y as i32,
+                x,
+                y,
                 width as u32,
                 height as u32,
                 0, /* border_width */
@@ -380,19 +1022,8 @@ impl Window {
                 ));
             }
 
-            let xn_input_style = CStr::from_bytes_with_nul_unchecked(XNInputStyle_0);
-            let xn_client_window = CStr::from_bytes_with_nul_unchecked(XNClientWindow_0);
-            let xn_focus_window = CStr::from_bytes_with_nul_unchecked(XNFocusWindow_0);
-            let xic = (d.lib.XCreateIC)(
-                xim,
-                xn_input_style.as_ptr(),
-                XIMPreeditNothing | XIMStatusNothing,
-                xn_client_window.as_ptr(),
-                handle as c_ulong,
-                xn_focus_window.as_ptr(),
-                handle as c_ulong,
-                std::ptr::null_mut::<c_void>(),
-            );
+            let mut ime_preedit = Box::<ImePreedit>::default();
+            let xic = Self::create_ic(&d, xim, handle, &mut *ime_preedit);
             if (xic as usize) == 0 {
                 return Err(Error::WindowCreate(
                     "Failed to setup X IC via XCreateIC.".to_owned(),
@@ -418,7 +1049,11 @@ impl Window {
                     | xlib::KeyReleaseMask
                     | xlib::ButtonPressMask
                     | xlib::ButtonReleaseMask
-                    | xlib::FocusChangeMask,
+                    | xlib::PointerMotionMask
+                    | xlib::EnterWindowMask
+                    | xlib::LeaveWindowMask
+                    | xlib::FocusChangeMask
+                    | xlib::PropertyChangeMask,
             );
 
             if !opts.resize || opts.none {
@@ -460,8 +1095,33 @@ impl Window {
             }
 
             (d.lib.XClearWindow)(d.display, handle);
-            (d.lib.XMapRaised)(d.display, handle);
             (d.lib.XSetWMProtocols)(d.display, handle, &mut d.wm_delete_window, 1);
+
+            // tell drag and drop sources that we accept drops
+            (d.lib.XChangeProperty)(
+                d.display,
+                handle,
+                d.xdnd.aware,
+                xlib::XA_ATOM,
+                32,
+                xlib::PropModeReplace,
+                &XDND_VERSION as *const c_long as *const c_uchar,
+                1,
+            );
+
+            if let (Some(xinput2), true) = (&d.xinput2, d.xinput2_touch) {
+                let mut mask = [0u8; 4];
+                xinput2::XISetMask(&mut mask, xinput2::XI_TouchBegin);
+                xinput2::XISetMask(&mut mask, xinput2::XI_TouchUpdate);
+                xinput2::XISetMask(&mut mask, xinput2::XI_TouchEnd);
+                let mut event_mask = xinput2::XIEventMask {
+                    deviceid: xinput2::XIAllMasterDevices,
+                    mask_len: mask.len() as c_int,
+                    mask: mask.as_mut_ptr(),
+                };
+                (xinput2.XISelectEvents)(d.display, handle, &mut event_mask, 1);
+            }
+
             (d.lib.XFlush)(d.display);
 
             let mut draw_buffer: Vec<u32> = Vec::new();
@@ -476,15 +1136,33 @@ impl Window {
                 }
             };
 
-            Ok(Window {
+            let mut window = Window {
                 d,
                 handle,
                 xim,
                 xic,
+                ime_preedit,
+                clipboard: Vec::new(),
+                primary: Vec::new(),
+                incr_transfers: Vec::new(),
+                drag: None,
                 ximage,
                 draw_buffer,
                 width: width as u32,
                 height: height as u32,
+                fixed_size: if !opts.resize || opts.none {
+                    Some((width as u32, height as u32))
+                } else {
+                    None
+                },
+                min_size: None,
+                max_size: None,
+                aspect_ratio: None,
+                fullscreen: Fullscreen::Off,
+                saved_crtc: None,
+                mapped: false,
+                maximized: false,
+                minimized: false,
                 scale: scale as i32,
                 mouse_x: 0.0,
                 mouse_y: 0.0,
@@ -494,14 +1172,171 @@ impl Window {
                 scale_mode: opts.scale_mode,
                 buttons: [0, 0, 0, 0, 0],
                 prev_cursor: CursorStyle::Arrow,
+                custom_cursor: 0,
+                cursor_visible: true,
+                cursor_grab: CursorGrab::None,
+                lock_pos: (0, 0),
+                lock_moved: false,
+                mouse_delta: (0.0, 0.0),
+                clicks: ClickCounter::new(),
+                touches: Vec::new(),
+                #[cfg(all(feature = "gamepad", target_os = "linux"))]
+                gamepads: Gamepads::new(),
+                pen_devices: Vec::new(),
+                scroll_devices: Vec::new(),
+                emulated_wheel: Vec::new(),
+                pen: None,
                 should_close: false,
+                intercept_close: opts.intercept_close,
+                close_requested: false,
                 active: false,
                 key_handler: KeyHandler::new(),
+                auto_repeat_keys: [0xff; 32],
+                events: EventBuffer::new(),
+                last_server_time: xlib::CurrentTime,
                 update_rate: UpdateRate::new(),
                 menu_counter: MenuHandle(0),
                 menus: Vec::new(),
-            })
+            };
+
+            window.select_devices();
+            window.read_xsettings();
+            window.read_key_repeat();
+
+            if opts.fullscreen != Fullscreen::Off {
+                window.set_fullscreen(opts.fullscreen)?;
+            }
+            if opts.topmost {
+                window.topmost(true)?;
+            }
+
+            (window.d.lib.XMapRaised)(window.d.display, window.handle);
+            (window.d.lib.XFlush)(window.d.display);
+            window.mapped = true;
+
+            Ok(window)
+        }
+    }
+
+    /// Creates the input context. Styles where the preedit text is reported to us (on-the-spot)
+    /// are preferred over the input method drawing it at the text cursor (over-the-spot) which
+    /// in turn is preferred over it being drawn in a separate window.
+    unsafe fn create_ic(
+        d: &DisplayInfo,
+        xim: XIM,
+        handle: xlib::Window,
+        ime_preedit: *mut ImePreedit,
+    ) -> XIC {
+        let xn_input_style = CStr::from_bytes_with_nul_unchecked(XNInputStyle_0);
+        let xn_client_window = CStr::from_bytes_with_nul_unchecked(XNClientWindow_0);
+        let xn_focus_window = CStr::from_bytes_with_nul_unchecked(XNFocusWindow_0);
+        let xn_preedit_attributes = CStr::from_bytes_with_nul_unchecked(XNPreeditAttributes_0);
+
+        let mut supported_styles = Vec::new();
+        let mut styles: *mut XIMStyles = std::ptr::null_mut();
+        let xn_query_input_style = CStr::from_bytes_with_nul_unchecked(XNQueryInputStyle_0);
+        if (d.lib.XGetIMValues)(
+            xim,
+            xn_query_input_style.as_ptr(),
+            &mut styles,
+            std::ptr::null_mut::<c_void>(),
+        )
+        .is_null()
+            && !styles.is_null()
+        {
+            supported_styles.extend_from_slice(std::slice::from_raw_parts(
+                (*styles).supported_styles,
+                (*styles).count_styles as usize,
+            ));
+            (d.lib.XFree)(styles as *mut c_void);
+        }
+
+        let client_data = ime_preedit as XPointer;
+        let start_callback = xlib::XICCallback {
+            client_data,
+            callback: Some(preedit_start_callback),
+        };
+        let done_callback = xlib::XIMCallback {
+            client_data,
+            callback: Some(preedit_done_callback),
+        };
+        let draw_callback = xlib::XIMCallback {
+            client_data,
+            callback: Some(preedit_draw_callback),
+        };
+        let caret_callback = xlib::XIMCallback {
+            client_data,
+            callback: Some(preedit_caret_callback),
+        };
+        let spot = xlib::XPoint { x: 0, y: 0 };
+
+        for preedit_style in [XIMPreeditCallbacks, XIMPreeditPosition, XIMPreeditNothing] {
+            let style = (preedit_style | XIMStatusNothing) as XIMStyle;
+
+            // PreeditNothing is always tried as some input methods doesn't report their styles
+            if preedit_style != XIMPreeditNothing && !supported_styles.contains(&style) {
+                continue;
+            }
+
+            // the callbacks and spot location are copied by Xlib so they don't need to outlive this
+            let attributes = if preedit_style == XIMPreeditCallbacks {
+                (d.lib.XVaCreateNestedList)(
+                    0,
+                    XNPreeditStartCallback_0.as_ptr(),
+                    &start_callback,
+                    XNPreeditDoneCallback_0.as_ptr(),
+                    &done_callback,
+                    XNPreeditDrawCallback_0.as_ptr(),
+                    &draw_callback,
+                    XNPreeditCaretCallback_0.as_ptr(),
+                    &caret_callback,
+                    std::ptr::null_mut::<c_void>(),
+                )
+            } else if preedit_style == XIMPreeditPosition {
+                (d.lib.XVaCreateNestedList)(
+                    0,
+                    XNSpotLocation_0.as_ptr(),
+                    &spot,
+                    std::ptr::null_mut::<c_void>(),
+                )
+            } else {
+                std::ptr::null_mut()
+            };
+
+            let xic = if attributes.is_null() {
+                (d.lib.XCreateIC)(
+                    xim,
+                    xn_input_style.as_ptr(),
+                    style,
+                    xn_client_window.as_ptr(),
+                    handle as c_ulong,
+                    xn_focus_window.as_ptr(),
+                    handle as c_ulong,
+                    std::ptr::null_mut::<c_void>(),
+                )
+            } else {
+                let xic = (d.lib.XCreateIC)(
+                    xim,
+                    xn_input_style.as_ptr(),
+                    style,
+                    xn_client_window.as_ptr(),
+                    handle as c_ulong,
+                    xn_focus_window.as_ptr(),
+                    handle as c_ulong,
+                    xn_preedit_attributes.as_ptr(),
+                    attributes,
+                    std::ptr::null_mut::<c_void>(),
+                );
+                (d.lib.XFree)(attributes);
+                xic
+            };
+
+            if !xic.is_null() {
+                return xic;
+            }
         }
+
+        std::ptr::null_mut()
     }
 
     unsafe fn alloc_image(
@@ -600,10 +1435,23 @@ impl Window {
         // clear before processing new events
         self.scroll_x = 0.0;
         self.scroll_y = 0.0;
+        self.mouse_delta = (0.0, 0.0);
+        self.events.clear();
+        #[cfg(all(feature = "gamepad", target_os = "linux"))]
+        self.gamepads.update(&mut self.events);
 
         unsafe {
-            self.raw_get_mouse_pos();
             self.raw_process_events();
+            self.process_key_repeat();
+            // the motion events only covers the pointer being inside the window
+            self.raw_get_mouse_pos();
+
+            if self.lock_moved && self.active {
+                let (x, y) = self.lock_pos;
+                (self.d.lib.XWarpPointer)(self.d.display, 0, self.handle, 0, 0, 0, 0, x, y);
+                (self.d.lib.XFlush)(self.d.display);
+            }
+            self.lock_moved = false;
         }
     }
 
@@ -641,13 +1489,15 @@ impl Window {
 
     #[inline]
     pub fn set_cursor_visibility(&mut self, visibility: bool) {
+        self.cursor_visible = visibility;
         unsafe {
             if visibility {
-                (self.d.lib.XDefineCursor)(
-                    self.d.display,
-                    self.handle,
-                    self.d.cursors[self.prev_cursor as usize],
-                );
+                let cursor = if self.custom_cursor != 0 {
+                    self.custom_cursor
+                } else {
+                    self.d.cursors[self.prev_cursor as usize]
+                };
+                (self.d.lib.XDefineCursor)(self.d.display, self.handle, cursor);
             } else {
                 static empty: [c_char; 8] = [0; 8];
                 let mut color = std::mem::zeroed();
@@ -680,40 +1530,351 @@ impl Window {
         }
     }
 
-    #[inline]
-    pub fn get_position(&self) -> (isize, isize) {
-        let (x, y);
-        let (mut nx, mut ny) = (0, 0);
+    pub fn set_fullscreen(&mut self, fullscreen: Fullscreen) -> Result<()> {
+        unsafe {
+            let crtcs = self.d.crtcs();
+            let crtc = match fullscreen {
+                Fullscreen::Off => None,
+                Fullscreen::Borderless(Some(index)) => Some(crtcs.get(index).ok_or_else(|| {
+                    Error::NotSupported(format!("There is no monitor with index {}", index))
+                })?),
+                Fullscreen::Borderless(None) | Fullscreen::Exclusive(_) => {
+                    self.current_crtc(&crtcs)
+                }
+            };
 
-        // Create dummy window for child_return value
-        let mut dummy_window: MaybeUninit<Window> = MaybeUninit::uninit();
-        let mut attributes: MaybeUninit<XWindowAttributes> = MaybeUninit::uninit();
+            // the new video mode is looked up before anything is changed
+            let mode = match fullscreen {
+                Fullscreen::Exclusive(video_mode) => {
+                    let crtc = crtc.ok_or_else(|| {
+                        Error::NotSupported("Changing the video mode requires XRandR".to_owned())
+                    })?;
+                    let mode = self.d.find_video_mode(crtc, video_mode).ok_or_else(|| {
+                        Error::NotSupported(format!(
+                            "The video mode {:?} isn't available",
+                            video_mode
+                        ))
+                    })?;
+                    Some((crtc, mode))
+                }
+                _ => None,
+            };
 
-        unsafe {
-            let root = (self.d.lib.XDefaultRootWindow)(self.d.display);
+            // the original mode is kept when the mode of the same monitor is changed again
+            let original = self.saved_crtc.clone();
+            self.restore_video_mode();
 
-            (self.d.lib.XGetWindowAttributes)(self.d.display, self.handle, attributes.as_mut_ptr());
-            x = attributes.assume_init().x;
-            y = attributes.assume_init().y;
+            if let Some((crtc, mode)) = mode {
+                if !self.d.set_crtc_mode(crtc, mode) {
+                    // the old mode is already restored, so the window leaves fullscreen too
+                    self.set_fullscreen(Fullscreen::Off)?;
+                    return Err(Error::NotSupported(
+                        "Failed to change the video mode".to_owned(),
+                    ));
+                }
 
-            (self.d.lib.XTranslateCoordinates)(
-                self.d.display,
-                self.handle,
-                root,
-                x,
-                y,
-                &mut nx,
-                &mut ny,
-                dummy_window.as_mut_ptr() as *mut c_ulong,
-            );
-        }
+                self.saved_crtc = Some(
+                    original
+                        .filter(|original| original.id == crtc.id)
+                        .unwrap_or_else(|| crtc.clone()),
+                );
+            }
 
-        (nx as isize, ny as isize)
-    }
+            // the window manager makes the window fullscreen on the monitor it's on, so it's
+            // moved there first (which isn't allowed while it's fullscreen)
+            if self.fullscreen != Fullscreen::Off {
+                self.send_wm_state(false, &[self.d.net_wm.state_fullscreen]);
+            }
+            if let Some(crtc) = crtc {
+                (self.d.lib.XMoveWindow)(self.d.display, self.handle, crtc.x, crtc.y);
+            }
 
-    #[inline]
-    pub fn get_size(&self) -> (usize, usize) {
-        (self.width as usize, self.height as usize)
+            self.fullscreen = fullscreen;
+            self.update_size_hints();
+
+            if fullscreen != Fullscreen::Off {
+                self.send_wm_state(true, &[self.d.net_wm.state_fullscreen]);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn topmost(&self, topmost: bool) -> Result<()> {
+        unsafe {
+            if topmost && !self.is_wm_supported(self.d.net_wm.state_above) {
+                return Err(Error::NotSupported(
+                    "The window manager can't keep windows on top".to_owned(),
+                ));
+            }
+            self.send_wm_state(topmost, &[self.d.net_wm.state_above]);
+        }
+        Ok(())
+    }
+
+    /// Checks if the window manager lists `hint` in `_NET_SUPPORTED`
+    unsafe fn is_wm_supported(&self, hint: xlib::Atom) -> bool {
+        let root = (self.d.lib.XDefaultRootWindow)(self.d.display);
+        self.read_property(root, self.d.net_wm.supported, false)
+            .is_some_and(|(_, data)| atoms_from_bytes(&data).contains(&hint))
+    }
+
+    pub fn set_maximized(&mut self, maximized: bool) {
+        let states = [
+            self.d.net_wm.state_maximized_vert,
+            self.d.net_wm.state_maximized_horz,
+        ];
+        unsafe { self.send_wm_state(maximized, &states) };
+    }
+
+    pub fn set_minimized(&mut self, minimized: bool) {
+        unsafe {
+            if minimized {
+                (self.d.lib.XIconifyWindow)(self.d.display, self.handle, self.d.screen);
+            } else {
+                // mapping an iconified window asks the window manager to restore it
+                (self.d.lib.XMapRaised)(self.d.display, self.handle);
+            }
+            (self.d.lib.XFlush)(self.d.display);
+        }
+    }
+
+    #[inline]
+    pub fn is_maximized(&self) -> bool {
+        self.maximized
+    }
+
+    #[inline]
+    pub fn is_minimized(&self) -> bool {
+        self.minimized
+    }
+
+    /// Reads the state of the window set by the window manager and reports the changes
+    unsafe fn read_wm_state(&mut self) {
+        let states = match self.read_property(self.handle, self.d.net_wm.state, false) {
+            Some((_, data)) => atoms_from_bytes(&data),
+            None => return,
+        };
+
+        let maximized = states.contains(&self.d.net_wm.state_maximized_vert)
+            && states.contains(&self.d.net_wm.state_maximized_horz);
+        let minimized = states.contains(&self.d.net_wm.state_hidden);
+
+        if maximized != self.maximized {
+            self.maximized = maximized;
+            self.events.push(Event::Maximized(maximized));
+        }
+        if minimized != self.minimized {
+            self.minimized = minimized;
+            self.events.push(Event::Minimized(minimized));
+        }
+    }
+
+    pub fn monitors(&self) -> Vec<Monitor> {
+        unsafe { self.d.monitors() }
+            .into_iter()
+            .map(|(monitor, _)| monitor)
+            .collect()
+    }
+
+    pub fn current_monitor(&self) -> Option<Monitor> {
+        let (x, y) = self.center();
+        let mut monitors = unsafe { self.d.monitors() };
+        let index = monitors
+            .iter()
+            .position(|(_, crtc)| crtc.as_ref().is_some_and(|crtc| crtc.contains(x, y)))
+            .unwrap_or(0);
+
+        Some(monitors.swap_remove(index).0)
+    }
+
+    /// The center of the window on the screen
+    fn center(&self) -> (c_int, c_int) {
+        let (x, y) = self.get_position();
+        (
+            x as c_int + self.width as c_int / 2,
+            y as c_int + self.height as c_int / 2,
+        )
+    }
+
+    /// Returns the CRTC that has the center of the window, or the first one
+    fn current_crtc<'a>(&self, crtcs: &'a [Crtc]) -> Option<&'a Crtc> {
+        let (x, y) = self.center();
+        crtcs
+            .iter()
+            .find(|crtc| crtc.contains(x, y))
+            .or_else(|| crtcs.first())
+    }
+
+    unsafe fn restore_video_mode(&mut self) {
+        if let Some(crtc) = self.saved_crtc.take() {
+            self.d.set_crtc_mode(&crtc, crtc.mode);
+        }
+    }
+
+    pub fn set_size(&mut self, width: usize, height: usize) {
+        let (width, height) = (width.max(1) as u32, height.max(1) as u32);
+
+        unsafe {
+            if self.fixed_size.is_some() {
+                self.fixed_size = Some((width, height));
+                self.update_size_hints();
+            }
+
+            // the size is updated when the window manager has resized the window
+            (self.d.lib.XResizeWindow)(self.d.display, self.handle, width, height);
+            (self.d.lib.XFlush)(self.d.display);
+        }
+    }
+
+    pub fn set_min_size(&mut self, size: Option<(usize, usize)>) {
+        self.min_size = size.map(|(width, height)| (width as u32, height as u32));
+        unsafe { self.update_size_hints() };
+    }
+
+    pub fn set_max_size(&mut self, size: Option<(usize, usize)>) {
+        self.max_size = size.map(|(width, height)| (width as u32, height as u32));
+        unsafe { self.update_size_hints() };
+    }
+
+    pub fn set_aspect_ratio(&mut self, ratio: Option<(usize, usize)>) {
+        self.aspect_ratio = ratio.map(|(width, height)| (width as u32, height as u32));
+        unsafe { self.update_size_hints() };
+    }
+
+    /// Tells the window manager the size limits of the window. A window with a fixed size is
+    /// kept at that size, and there are no limits while it's fullscreen.
+    unsafe fn update_size_hints(&mut self) {
+        let mut size_hints: xlib::XSizeHints = std::mem::zeroed();
+
+        let (min_size, max_size) = match self.fixed_size {
+            Some(size) => (Some(size), Some(size)),
+            None => (self.min_size, self.max_size),
+        };
+
+        if self.fullscreen == Fullscreen::Off {
+            if let Some((width, height)) = min_size {
+                size_hints.flags |= xlib::PMinSize;
+                size_hints.min_width = width as i32;
+                size_hints.min_height = height as i32;
+            }
+            if let Some((width, height)) = max_size {
+                size_hints.flags |= xlib::PMaxSize;
+                size_hints.max_width = width as i32;
+                size_hints.max_height = height as i32;
+            }
+            if let (Some((width, height)), None) = (self.aspect_ratio, self.fixed_size) {
+                size_hints.flags |= xlib::PAspect;
+                size_hints.min_aspect.x = width as i32;
+                size_hints.min_aspect.y = height as i32;
+                size_hints.max_aspect = size_hints.min_aspect;
+            }
+        }
+
+        (self.d.lib.XSetWMNormalHints)(self.d.display, self.handle, &mut size_hints);
+        (self.d.lib.XFlush)(self.d.display);
+    }
+
+    /// Adds or removes one or two states of the window (`_NET_WM_STATE`). The window manager
+    /// reads the property when the window is mapped, after that it has to be asked to change it.
+    unsafe fn send_wm_state(&self, add: bool, states: &[xlib::Atom]) {
+        const NET_WM_STATE_REMOVE: c_long = 0;
+        const NET_WM_STATE_ADD: c_long = 1;
+        // tells the window manager that the request comes from an application
+        const SOURCE_APPLICATION: c_long = 1;
+
+        if !self.mapped {
+            let mut current = match self.read_property(self.handle, self.d.net_wm.state, false) {
+                Some((_, data)) => atoms_from_bytes(&data),
+                None => Vec::new(),
+            };
+            current.retain(|state| !states.contains(state));
+            if add {
+                current.extend_from_slice(states);
+            }
+
+            (self.d.lib.XChangeProperty)(
+                self.d.display,
+                self.handle,
+                self.d.net_wm.state,
+                xlib::XA_ATOM,
+                32,
+                xlib::PropModeReplace,
+                current.as_ptr() as *const c_uchar,
+                current.len() as c_int,
+            );
+            return;
+        }
+
+        let mut message = xlib::XClientMessageEvent {
+            type_: xlib::ClientMessage,
+            serial: 0,
+            send_event: xlib::True,
+            display: self.d.display,
+            window: self.handle,
+            message_type: self.d.net_wm.state,
+            format: 32,
+            data: xlib::ClientMessageData::new(),
+        };
+        let action = if add {
+            NET_WM_STATE_ADD
+        } else {
+            NET_WM_STATE_REMOVE
+        };
+        message.data.as_longs_mut()[..4].copy_from_slice(&[
+            action,
+            states.first().copied().unwrap_or(0) as c_long,
+            states.get(1).copied().unwrap_or(0) as c_long,
+            SOURCE_APPLICATION,
+        ]);
+
+        let mut event = xlib::XEvent {
+            client_message: message,
+        };
+        (self.d.lib.XSendEvent)(
+            self.d.display,
+            (self.d.lib.XDefaultRootWindow)(self.d.display),
+            xlib::False,
+            xlib::SubstructureRedirectMask | xlib::SubstructureNotifyMask,
+            &mut event,
+        );
+        (self.d.lib.XFlush)(self.d.display);
+    }
+
+    #[inline]
+    pub fn get_position(&self) -> (isize, isize) {
+        let (x, y);
+        let (mut nx, mut ny) = (0, 0);
+
+        // Create dummy window for child_return value
+        let mut dummy_window: MaybeUninit<Window> = MaybeUninit::uninit();
+        let mut attributes: MaybeUninit<XWindowAttributes> = MaybeUninit::uninit();
+
+        unsafe {
+            let root = (self.d.lib.XDefaultRootWindow)(self.d.display);
+
+            (self.d.lib.XGetWindowAttributes)(self.d.display, self.handle, attributes.as_mut_ptr());
+            x = attributes.assume_init().x;
+            y = attributes.assume_init().y;
+
+            (self.d.lib.XTranslateCoordinates)(
+                self.d.display,
+                self.handle,
+                root,
+                x,
+                y,
+                &mut nx,
+                &mut ny,
+                dummy_window.as_mut_ptr() as *mut c_ulong,
+            );
+        }
+
+        (nx as isize, ny as isize)
+    }
+
+    #[inline]
+    pub fn get_size(&self) -> (usize, usize) {
+        (self.width as usize, self.height as usize)
     }
 
     pub fn get_mouse_pos(&self, mode: MouseMode) -> Option<(f32, f32)> {
@@ -749,21 +1910,154 @@ impl Window {
         }
     }
 
+    pub fn set_cursor_grab(&mut self, grab: CursorGrab) -> Result<()> {
+        unsafe {
+            (self.d.lib.XUngrabPointer)(self.d.display, xlib::CurrentTime);
+            self.cursor_grab = CursorGrab::None;
+
+            if grab != CursorGrab::None {
+                self.grab_pointer()?;
+            }
+        }
+
+        if grab == CursorGrab::Locked {
+            self.lock_pos = (
+                (self.mouse_x as c_int).clamp(0, self.width as c_int - 1),
+                (self.mouse_y as c_int).clamp(0, self.height as c_int - 1),
+            );
+        }
+
+        self.cursor_grab = grab;
+        Ok(())
+    }
+
+    unsafe fn grab_pointer(&mut self) -> Result<()> {
+        let result = (self.d.lib.XGrabPointer)(
+            self.d.display,
+            self.handle,
+            xlib::True,
+            (xlib::ButtonPressMask | xlib::ButtonReleaseMask | xlib::PointerMotionMask) as c_uint,
+            xlib::GrabModeAsync,
+            xlib::GrabModeAsync,
+            // confine the pointer to the window
+            self.handle,
+            0,
+            xlib::CurrentTime,
+        );
+
+        if result == xlib::GrabSuccess {
+            Ok(())
+        } else {
+            Err(Error::UpdateFailed(format!(
+                "XGrabPointer failed: {}",
+                result
+            )))
+        }
+    }
+
+    #[inline]
+    pub fn get_mouse_delta(&self) -> (f32, f32) {
+        self.mouse_delta
+    }
+
+    #[inline]
+    pub fn get_touches(&self) -> &[Touch] {
+        &self.touches
+    }
+
+    #[inline]
+    pub fn get_click_count(&self, button: MouseButton) -> u32 {
+        self.clicks.get(button)
+    }
+
+    #[inline]
+    pub fn set_default_double_click_interval(&mut self, interval: Duration) {
+        self.clicks.default_interval = u32::try_from(interval.as_millis()).unwrap_or(u32::MAX);
+    }
+
+    #[cfg(all(feature = "gamepad", target_os = "linux"))]
+    #[inline]
+    pub fn get_gamepads(&self) -> &[crate::Gamepad] {
+        self.gamepads.get_gamepads()
+    }
+
+    #[inline]
+    pub fn get_pen_state(&self) -> Option<PenState> {
+        self.pen
+    }
+
+    pub fn set_mouse_pos(&mut self, x: f32, y: f32) -> Result<()> {
+        let scale = self.scale as f32;
+        let (x, y) = ((x * scale) as c_int, (y * scale) as c_int);
+
+        unsafe {
+            (self.d.lib.XWarpPointer)(self.d.display, 0, self.handle, 0, 0, 0, 0, x, y);
+            (self.d.lib.XFlush)(self.d.display);
+        }
+
+        // a locked cursor stays at the new position
+        self.lock_pos = (x, y);
+        self.mouse_x = x as f32;
+        self.mouse_y = y as f32;
+        Ok(())
+    }
+
     #[inline]
     pub fn set_cursor_style(&mut self, cursor: CursorStyle) {
-        if self.prev_cursor != cursor {
+        if self.prev_cursor != cursor || self.custom_cursor != 0 {
             unsafe {
-                (self.d.lib.XDefineCursor)(
-                    self.d.display,
-                    self.handle,
-                    self.d.cursors[cursor as usize],
-                );
+                if self.cursor_visible {
+                    (self.d.lib.XDefineCursor)(
+                        self.d.display,
+                        self.handle,
+                        self.d.cursors[cursor as usize],
+                    );
+                }
+                self.free_custom_cursor();
             }
 
             self.prev_cursor = cursor;
         }
     }
 
+    pub fn set_custom_cursor(&mut self, cursor: &CustomCursor) {
+        unsafe {
+            let image = (self.d.cursor_lib.XcursorImageCreate)(
+                cursor.width as c_int,
+                cursor.height as c_int,
+            );
+            if image.is_null() {
+                return;
+            }
+
+            (*image).xhot = cursor.hotspot.0 as _;
+            (*image).yhot = cursor.hotspot.1 as _;
+
+            // Xcursor expects premultiplied alpha
+            let pixels = std::slice::from_raw_parts_mut((*image).pixels, cursor.data.len());
+            for (pixel, &argb) in pixels.iter_mut().zip(&cursor.data) {
+                *pixel = premultiply_alpha(argb);
+            }
+
+            let handle = (self.d.cursor_lib.XcursorImageLoadCursor)(self.d.display, image);
+            (self.d.cursor_lib.XcursorImageDestroy)(image);
+
+            // a hidden cursor is shown with the new image by set_cursor_visibility
+            if self.cursor_visible {
+                (self.d.lib.XDefineCursor)(self.d.display, self.handle, handle);
+            }
+            self.free_custom_cursor();
+            self.custom_cursor = handle;
+        }
+    }
+
+    unsafe fn free_custom_cursor(&mut self) {
+        if self.custom_cursor != 0 {
+            (self.d.lib.XFreeCursor)(self.d.display, self.custom_cursor);
+            self.custom_cursor = 0;
+        }
+    }
+
     #[inline]
     pub fn set_rate(&mut self, rate: Option<Duration>) {
         self.update_rate.set_rate(rate);
@@ -824,11 +2118,77 @@ impl Window {
         self.key_handler.set_input_callback(callback)
     }
 
+    #[inline]
+    pub fn poll_events(&self) -> std::slice::Iter<'_, TimedEvent> {
+        self.events.iter()
+    }
+
+    #[inline]
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, TimedEvent> {
+        self.events.drain()
+    }
+
+    #[inline]
+    pub fn set_resize_callback(&mut self, callback: Box<dyn FnMut(WindowSize)>) {
+        self.events.set_resize_callback(callback)
+    }
+
     #[inline]
     pub fn is_open(&self) -> bool {
         !self.should_close
     }
 
+    #[inline]
+    pub fn close_requested(&self) -> bool {
+        self.close_requested
+    }
+
+    #[inline]
+    pub fn cancel_close(&mut self) {
+        self.close_requested = false;
+    }
+
+    #[inline]
+    pub fn close(&mut self) {
+        self.close_requested = false;
+        self.should_close = true;
+    }
+
+    #[inline]
+    pub fn get_modifiers(&self) -> Modifiers {
+        self.key_handler.get_modifiers()
+    }
+
+    pub fn set_ime_position(&mut self, x: usize, y: usize) {
+        let clamp = |v: usize| (v as i64 * self.scale as i64).min(c_short::MAX as i64) as c_short;
+        let spot = xlib::XPoint {
+            x: clamp(x),
+            y: clamp(y),
+        };
+
+        unsafe {
+            let attributes = (self.d.lib.XVaCreateNestedList)(
+                0,
+                XNSpotLocation_0.as_ptr(),
+                &spot,
+                std::ptr::null_mut::<c_void>(),
+            );
+            // fails for input styles that doesn't use the spot location which is fine
+            (self.d.lib.XSetICValues)(
+                self.xic,
+                XNPreeditAttributes_0.as_ptr(),
+                attributes,
+                std::ptr::null_mut::<c_void>(),
+            );
+            (self.d.lib.XFree)(attributes);
+        }
+    }
+
+    #[inline]
+    pub fn is_scancode_down(&self, scancode: Scancode) -> bool {
+        self.key_handler.is_scancode_down(scancode)
+    }
+
     #[inline]
     pub fn is_active(&self) -> bool {
         self.active
@@ -998,8 +2358,32 @@ impl Window {
             &mut mask,
         ) != xlib::False
         {
-            self.mouse_x = child_x as f32;
-            self.mouse_y = child_y as f32;
+            self.key_handler
+                .set_modifiers(Self::modifiers_from_state(mask));
+
+            self.update_mouse_pos(child_x, child_y);
+        }
+    }
+
+    /// Updates the mouse position and reports it if it changed
+    fn update_mouse_pos(&mut self, x: c_int, y: c_int) {
+        // the position is frozen while locked and the pointer is moved back in update
+        if self.cursor_grab == CursorGrab::Locked {
+            self.lock_moved |= (x, y) != self.lock_pos;
+            return;
+        }
+
+        let (x, y) = (x as f32, y as f32);
+
+        if x != self.mouse_x || y != self.mouse_y {
+            self.mouse_x = x;
+            self.mouse_y = y;
+
+            let scale = self.scale as f32;
+            self.events.push(Event::MouseMove {
+                x: x / scale,
+                y: y / scale,
+            });
         }
     }
 
@@ -1013,7 +2397,14 @@ impl Window {
 
             //skip any events that need to get eaten by X to do compose key, e.g. if the user types compose key + a + ' then all of these events need to get eaten and processed in xlib
             //XFilterEvent will do the processing for these cases, and returns whether or not it handled an event
-            if (self.d.lib.XFilterEvent)(&mut event as *mut XEvent, 0) != 0 {
+            let filtered = (self.d.lib.XFilterEvent)(&mut event as *mut XEvent, 0) != 0;
+
+            // the input method may have updated the preedit text while filtering
+            for event in self.ime_preedit.events.drain(..) {
+                self.events.push(event);
+            }
+
+            if filtered {
                 continue;
             }
 
@@ -1024,26 +2415,72 @@ impl Window {
         }
     }
 
+    /// Keeps the time of the events that carries the server time. The time of the event buffer
+    /// can't be used for this as gamepads and key repeats uses other clocks.
+    unsafe fn update_server_time(&mut self, ev: &xlib::XEvent) {
+        let time = match ev.type_ {
+            xlib::KeyPress | xlib::KeyRelease => ev.key.time,
+            xlib::ButtonPress | xlib::ButtonRelease => ev.button.time,
+            xlib::MotionNotify => ev.motion.time,
+            xlib::EnterNotify | xlib::LeaveNotify => ev.crossing.time,
+            xlib::PropertyNotify => ev.property.time,
+            _ => return,
+        };
+
+        // skip synthetic events without a time
+        if time != xlib::CurrentTime {
+            self.last_server_time = time;
+        }
+    }
+
     unsafe fn raw_process_one_event(&mut self, mut ev: xlib::XEvent) -> ProcessEventResult {
+        // extension events doesn't have a window
+        if ev.type_ == xlib::GenericEvent {
+            self.process_generic_event(ev.generic_event_cookie);
+            return ProcessEventResult::Ok;
+        }
+
+        self.update_server_time(&ev);
+
+        // the requestors of large selections delete the property when they want the next chunk
+        if ev.type_ == xlib::PropertyNotify && ev.property.window != self.handle {
+            self.process_incr_transfer(&ev.property);
+            return ProcessEventResult::Ok;
+        }
+
         // FIXME: we cannot handle multiple windows here!
         if ev.any.window != self.handle {
             return ProcessEventResult::Ok;
         }
 
         match ev.type_ {
-            xlib::ClientMessage => {
-                // TODO : check for message_type == wm_protocols, as per x11-rs example
-                if ev.client_message.format == 32 /* i.e. longs */ &&
-                   ev.client_message.data.get_long(0) as xlib::Atom == self.d.wm_delete_window
-                {
+            // TODO : check for message_type == wm_protocols, as per x11-rs example
+            // (format 32 means the data is longs)
+            xlib::ClientMessage
+                if ev.client_message.format == 32
+                    && ev.client_message.data.get_long(0) as xlib::Atom
+                        == self.d.wm_delete_window =>
+            {
+                self.events.push(Event::CloseRequested);
+
+                if self.intercept_close {
+                    self.close_requested = true;
+                } else {
                     self.should_close = true;
                     return ProcessEventResult::Termination;
                 }
             }
+            xlib::ClientMessage => self.process_xdnd_message(&ev.client_message),
 
             xlib::KeyPress => {
-                self.process_key(ev, true /* is_down */);
-                self.emit_code_point_chars_to_callback(&mut ev.key);
+                self.events.set_time_ms(ev.key.time as u32);
+                // XIM sends the committed text as a fake key press without a keycode
+                if ev.key.keycode != 0 {
+                    self.key_handler
+                        .set_modifiers(Self::modifiers_from_state(ev.key.state));
+                    self.process_key(ev, true /* is_down */);
+                }
+                self.emit_code_point_chars(&mut ev.key);
             }
 
             xlib::KeyRelease => {
@@ -1070,21 +2507,48 @@ impl Window {
                 }
                  */
 
+                self.events.set_time_ms(ev.key.time as u32);
+                self.key_handler
+                    .set_modifiers(Self::modifiers_from_state(ev.key.state));
                 self.process_key(ev, false /* is_down */);
             }
 
             xlib::ButtonPress => {
+                self.events.set_time_ms(ev.button.time as u32);
+                self.update_mouse_pos(ev.button.x, ev.button.y);
                 self.process_button(ev, true /* is_down */);
             }
 
             xlib::ButtonRelease => {
+                self.events.set_time_ms(ev.button.time as u32);
+                self.update_mouse_pos(ev.button.x, ev.button.y);
                 self.process_button(ev, false /* is_down */);
             }
 
+            xlib::MotionNotify => {
+                self.events.set_time_ms(ev.motion.time as u32);
+                self.update_mouse_pos(ev.motion.x, ev.motion.y);
+            }
+
+            // the scroll valuators can change while the pointer is outside of the window
+            xlib::EnterNotify => self.read_scroll_valuators(),
+
+            // there is no proximity information for pens, leaving the window is the closest
+            xlib::LeaveNotify if self.pen.is_some() => {
+                self.pen = None;
+                self.events.set_time_ms(ev.crossing.time as u32);
+                self.events.push(Event::PenLeave);
+            }
+
             xlib::ConfigureNotify => {
-                // TODO : pass this onto the application
-                self.width = ev.configure.width as u32;
-                self.height = ev.configure.height as u32;
+                let width = ev.configure.width as u32;
+                let height = ev.configure.height as u32;
+
+                // ConfigureNotify is also sent when the window is moved
+                let resized = width != self.width || height != self.height;
+
+                self.width = width;
+                self.height = height;
                 self.free_image();
                 self.ximage = Self::alloc_image(
                     &self.d,
@@ -1093,12 +2557,42 @@ impl Window {
                     &mut self.draw_buffer,
                 )
                 .expect("todo");
+
+                if resized {
+                    self.events.push_resize(WindowSize::new(
+                        width as usize,
+                        height as usize,
+                        self.scale as usize,
+                    ));
+                }
             }
             xlib::FocusOut => {
                 self.active = false;
+                self.events.push(Event::Focus(false));
+                self.key_handler.cancel_repeat();
+
+                // let the user interact with other windows
+                (self.d.lib.XUngrabPointer)(self.d.display, xlib::CurrentTime);
             }
             xlib::FocusIn => {
                 self.active = true;
+                self.events.push(Event::Focus(true));
+
+                if self.cursor_grab != CursorGrab::None {
+                    let _ = self.grab_pointer();
+                }
+            }
+
+            xlib::PropertyNotify if ev.property.atom == self.d.net_wm.state => {
+                self.read_wm_state();
+            }
+
+            xlib::SelectionRequest => self.process_selection_request(&ev.selection_request),
+            // someone else took over the selection
+            xlib::SelectionClear => {
+                if let Some(data) = self.owned_selection_mut(ev.selection_clear.selection) {
+                    data.clear();
+                }
             }
 
             _ => {}
@@ -1110,6 +2604,9 @@ impl Window {
     fn process_key(&mut self, mut ev: xlib::XEvent, is_down: bool) {
         // NOTE: need "mut" on ev due to dumbness in the X API
 
+        // X11 keycodes are the evdev codes offset by 8
+        let scancode = Scancode(unsafe { ev.key.keycode }.saturating_sub(8));
+
         // handle special keys...
 
         if self.d.keyb_ext {
@@ -1126,7 +2623,7 @@ impl Window {
                 XK_KP_0 | XK_KP_1 | XK_KP_2 | XK_KP_3 | XK_KP_4 | XK_KP_5 | XK_KP_6 | XK_KP_7
                 | XK_KP_8 | XK_KP_9 | XK_KP_Separator | XK_KP_Decimal | XK_KP_Equal
                 | XK_KP_Enter => {
-                    self.update_key_state(sym, is_down);
+                    self.update_key_state(sym, scancode, is_down);
                     return;
                 }
 
@@ -1140,71 +2637,1090 @@ impl Window {
             (self.d.lib.XLookupKeysym)(&mut ev.key, 0 /* index */)
         };
 
-        if sym == xlib::NoSymbol as xlib::KeySym {
-            return;
+        // keys without a symbol are still reported with their scancode
+        self.update_key_state(sym, scancode, is_down);
+    }
+
+    fn modifiers_from_state(state: c_uint) -> Modifiers {
+        // Mod1 is Alt, Mod2 is Num Lock and Mod4 is Super with the default X keyboard mapping
+        let masks = [
+            (xlib::ShiftMask, Modifiers::SHIFT),
+            (xlib::ControlMask, Modifiers::CTRL),
+            (xlib::Mod1Mask, Modifiers::ALT),
+            (xlib::Mod4Mask, Modifiers::SUPER),
+            (xlib::LockMask, Modifiers::CAPS_LOCK),
+            (xlib::Mod2Mask, Modifiers::NUM_LOCK),
+        ];
+
+        let mut modifiers = Modifiers::empty();
+
+        for (mask, modifier) in masks {
+            if state & mask != 0 {
+                modifiers |= modifier;
+            }
         }
 
-        self.update_key_state(sym, is_down);
+        modifiers
     }
 
-    fn emit_code_point_chars_to_callback(&mut self, event: &mut XKeyEvent) {
-        const BUFFER_SIZE: usize = 32;
-
-        if let Some(callback) = &mut self.key_handler.key_callback {
-            let mut buff: [u8; BUFFER_SIZE] = [0; BUFFER_SIZE];
-            let str = unsafe {
-                let mut keysym: KeySym = std::mem::zeroed();
-                let mut status: Status = 0;
-                let length_in_bytes = (self.d.lib.Xutf8LookupString)(
+    fn emit_code_point_chars(&mut self, event: &mut XKeyEvent) {
+        // text committed by an input method can be longer than the initial buffer
+        let mut buff: Vec<u8> = vec![0; 32];
+        let length_in_bytes = loop {
+            let mut keysym: KeySym = 0;
+            let mut status: Status = 0;
+            let length_in_bytes = unsafe {
+                (self.d.lib.Xutf8LookupString)(
                     self.xic,
                     event as *mut XKeyEvent,
                     buff.as_mut_ptr() as *mut c_char,
-                    (BUFFER_SIZE - 1) as c_int,
+                    buff.len() as c_int,
                     (&mut keysym) as *mut KeySym,
                     (&mut status) as *mut Status,
-                );
-                &buff[0..(length_in_bytes as usize + 1)]
+                )
             };
 
-            if let Ok(cstr) = CStr::from_bytes_with_nul(str) {
-                if let Ok(str) = cstr.to_str() {
-                    for c in str.chars() {
-                        callback.add_char(c as u32);
-                    }
+            match status {
+                xlib::XBufferOverflow => buff.resize(length_in_bytes as usize, 0),
+                xlib::XLookupChars | xlib::XLookupBoth => break length_in_bytes as usize,
+                _ => return,
+            }
+        };
+
+        if let Ok(str) = std::str::from_utf8(&buff[..length_in_bytes]) {
+            if str.chars().any(|c| !c.is_control()) {
+                self.events.push(Event::Commit(str.to_owned()));
+            }
+
+            for c in str.chars() {
+                self.events.push(Event::Char(c));
+
+                if let Some(callback) = &mut self.key_handler.key_callback {
+                    callback.add_char(c as u32);
                 }
             }
         }
     }
 
-    unsafe fn process_button(&mut self, ev: xlib::XEvent, is_down: bool) {
-        match ev.button.button {
-            xlib::Button1 => {
-                self.buttons[0] = if is_down { 1 } else { 0 };
-                return;
+    /// Sends the data we own to another client asking for it
+    unsafe fn process_selection_request(&mut self, request: &xlib::XSelectionRequestEvent) {
+        let data = self.owned_selection(request.selection).unwrap_or_default();
+
+        // obsolete clients doesn't set the property and expects the target to be used instead
+        let property = if request.property == 0 {
+            request.target
+        } else {
+            request.property
+        };
+
+        let mut reply = xlib::XSelectionEvent {
+            type_: xlib::SelectionNotify,
+            serial: 0,
+            send_event: xlib::True,
+            display: request.display,
+            requestor: request.requestor,
+            selection: request.selection,
+            target: request.target,
+            property,
+            time: request.time,
+        };
+
+        if data.is_empty() {
+            reply.property = 0;
+        } else if request.target == self.d.targets {
+            let targets: Vec<xlib::Atom> = std::iter::once(self.d.targets)
+                .chain(data.iter().map(|(target, _)| *target))
+                .collect();
+
+            (self.d.lib.XChangeProperty)(
+                self.d.display,
+                request.requestor,
+                property,
+                xlib::XA_ATOM,
+                32,
+                xlib::PropModeReplace,
+                targets.as_ptr() as *const c_uchar,
+                targets.len() as c_int,
+            );
+        } else if let Some((_, bytes)) = data.iter().find(|(target, _)| *target == request.target) {
+            if bytes.len() > self.d.max_property_size() {
+                self.start_incr_transfer(
+                    request.requestor,
+                    property,
+                    request.target,
+                    bytes.to_vec(),
+                );
+            } else {
+                (self.d.lib.XChangeProperty)(
+                    self.d.display,
+                    request.requestor,
+                    property,
+                    request.target,
+                    8,
+                    xlib::PropModeReplace,
+                    bytes.as_ptr(),
+                    bytes.len() as c_int,
+                );
             }
-            xlib::Button2 => {
-                self.buttons[1] = if is_down { 1 } else { 0 };
-                return;
+        } else {
+            reply.property = 0;
+        }
+
+        let mut event = xlib::XEvent { selection: reply };
+        (self.d.lib.XSendEvent)(
+            self.d.display,
+            request.requestor,
+            xlib::False,
+            0,
+            &mut event,
+        );
+        (self.d.lib.XFlush)(self.d.display);
+    }
+
+    /// Starts sending a large selection. The INCR property tells the requestor to read the data
+    /// in chunks, which are sent by `process_incr_transfer`.
+    unsafe fn start_incr_transfer(
+        &mut self,
+        requestor: xlib::Window,
+        property: xlib::Atom,
+        target: xlib::Atom,
+        data: Vec<u8>,
+    ) {
+        // a new request for the same property replaces an unfinished transfer
+        self.incr_transfers
+            .retain(|transfer| transfer.requestor != requestor || transfer.property != property);
+
+        (self.d.lib.XSelectInput)(self.d.display, requestor, xlib::PropertyChangeMask);
+
+        // the value is a lower bound of the size
+        let size = data.len() as c_long;
+        (self.d.lib.XChangeProperty)(
+            self.d.display,
+            requestor,
+            property,
+            self.d.incr,
+            32,
+            xlib::PropModeReplace,
+            &size as *const c_long as *const c_uchar,
+            1,
+        );
+
+        self.incr_transfers.push(IncrTransfer {
+            requestor,
+            property,
+            target,
+            data,
+            offset: 0,
+        });
+    }
+
+    /// Sends the next chunk of a large selection once the requestor has deleted the property,
+    /// an empty chunk ends the transfer
+    unsafe fn process_incr_transfer(&mut self, event: &xlib::XPropertyEvent) {
+        if event.state != xlib::PropertyDelete {
+            return;
+        }
+
+        let index = match self.incr_transfers.iter().position(|transfer| {
+            transfer.requestor == event.window && transfer.property == event.atom
+        }) {
+            Some(index) => index,
+            None => return,
+        };
+
+        let chunk_size = self.d.max_property_size();
+        let transfer = &mut self.incr_transfers[index];
+        let end = (transfer.offset + chunk_size).min(transfer.data.len());
+        let chunk = &transfer.data[transfer.offset..end];
+
+        (self.d.lib.XChangeProperty)(
+            self.d.display,
+            transfer.requestor,
+            transfer.property,
+            transfer.target,
+            8,
+            xlib::PropModeReplace,
+            chunk.as_ptr(),
+            chunk.len() as c_int,
+        );
+
+        if chunk.is_empty() {
+            (self.d.lib.XSelectInput)(self.d.display, transfer.requestor, xlib::NoEventMask);
+            self.incr_transfers.swap_remove(index);
+        } else {
+            transfer.offset = end;
+        }
+
+        (self.d.lib.XFlush)(self.d.display);
+    }
+
+    unsafe fn process_generic_event(&mut self, mut cookie: xlib::XGenericEventCookie) {
+        if self.d.xinput2.is_none()
+            || cookie.extension != self.d.xinput2_opcode
+            || (self.d.lib.XGetEventData)(self.d.display, &mut cookie) == 0
+        {
+            return;
+        }
+
+        // raw motion is sent even if another window has focus
+        if cookie.evtype == xinput2::XI_RawMotion && self.active {
+            let raw = &*(cookie.data as *const xinput2::XIRawEvent);
+            let mask =
+                std::slice::from_raw_parts(raw.valuators.mask, raw.valuators.mask_len as usize);
+
+            // the values are only present for the axes set in the mask, x and y are the first two
+            let mut values = raw.raw_values;
+            for axis in 0..mask.len() * 8 {
+                if mask[axis / 8] & (1 << (axis % 8)) == 0 {
+                    continue;
+                }
+
+                match axis {
+                    0 => self.mouse_delta.0 += *values as f32,
+                    1 => self.mouse_delta.1 += *values as f32,
+                    _ => break,
+                }
+                values = values.add(1);
             }
-            xlib::Button3 => {
-                self.buttons[2] = if is_down { 1 } else { 0 };
-                return;
+        }
+
+        match cookie.evtype {
+            xinput2::XI_TouchBegin => self.process_touch(&cookie, TouchPhase::Begin),
+            xinput2::XI_TouchUpdate => self.process_touch(&cookie, TouchPhase::Move),
+            xinput2::XI_TouchEnd => self.process_touch(&cookie, TouchPhase::End),
+            xinput2::XI_Motion => {
+                self.process_pen_event(&cookie);
+                self.process_scroll_event(&cookie);
+            }
+            xinput2::XI_ButtonPress | xinput2::XI_ButtonRelease => {
+                self.process_pen_event(&cookie);
+                self.process_emulated_wheel(&cookie);
             }
+            xinput2::XI_HierarchyChanged => self.select_devices(),
+            _ => (),
+        }
+
+        (self.d.lib.XFreeEventData)(self.d.display, &mut cookie);
+    }
 
-            Button8 => {
-                self.buttons[3] = if is_down { 1 } else { 0 };
+    /// Finds the tablet pens (devices with a pressure axis) and the devices with smooth
+    /// scrolling and selects their events. The core events of the master pointer are still sent
+    /// so these devices keeps moving the mouse.
+    fn select_devices(&mut self) {
+        let xinput2 = match &self.d.xinput2 {
+            Some(xinput2) => xinput2,
+            None => return,
+        };
+        let [pressure_label, tilt_x_label, tilt_y_label] = self.d.pen_axes;
+
+        self.pen_devices.clear();
+        self.scroll_devices.clear();
+
+        unsafe {
+            let mut count = 0;
+            let infos = (xinput2.XIQueryDevice)(self.d.display, xinput2::XIAllDevices, &mut count);
+            if infos.is_null() {
                 return;
             }
 
-            Button9 => {
-                self.buttons[4] = if is_down { 1 } else { 0 };
-                return;
+            for info in std::slice::from_raw_parts(infos, count as usize) {
+                if info._use != xinput2::XISlavePointer && info._use != xinput2::XIFloatingSlave {
+                    continue;
+                }
+
+                let name = CStr::from_ptr(info.name).to_string_lossy().to_lowercase();
+                let mut pen = PenDevice {
+                    id: info.deviceid,
+                    eraser: name.contains("eraser"),
+                    pressure: None,
+                    tilt_x: None,
+                    tilt_y: None,
+                };
+                // X11 doesn't report the scroll source, guess it from the name
+                let source = if name.contains("touchpad") || name.contains("trackpad") {
+                    ScrollSource::Finger
+                } else if name.contains("trackpoint") || name.contains("pointing stick") {
+                    ScrollSource::Continuous
+                } else {
+                    ScrollSource::Wheel
+                };
+                let mut scroll = ScrollDevice {
+                    id: info.deviceid,
+                    source,
+                    axes: [None; 2],
+                };
+
+                let classes = std::slice::from_raw_parts(info.classes, info.num_classes as usize);
+                for &class in classes {
+                    match (*class)._type {
+                        xinput2::XIValuatorClass => {
+                            let valuator = &*(class as *const xinput2::XIValuatorClassInfo);
+                            let label = valuator.label;
+                            if label == 0 {
+                                continue;
+                            }
+
+                            if label == pressure_label {
+                                pen.pressure = Some((valuator.number, valuator.min, valuator.max));
+                            } else if label == tilt_x_label {
+                                pen.tilt_x = Some(valuator.number);
+                            } else if label == tilt_y_label {
+                                pen.tilt_y = Some(valuator.number);
+                            }
+                        }
+                        xinput2::XIScrollClass => {
+                            let class = &*(class as *const xinput2::XIScrollClassInfo);
+                            let axis = match class.scroll_type {
+                                xinput2::XIScrollTypeHorizontal => 0,
+                                xinput2::XIScrollTypeVertical => 1,
+                                _ => continue,
+                            };
+                            if class.increment != 0.0 {
+                                scroll.axes[axis] = Some(ScrollValuator {
+                                    number: class.number,
+                                    increment: class.increment,
+                                    last: None,
+                                });
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+
+                if pen.pressure.is_some() {
+                    self.pen_devices.push(pen);
+                }
+                if scroll.axes.iter().any(Option::is_some) {
+                    self.scroll_devices.push(scroll);
+                }
             }
 
-            _ => {}
+            (xinput2.XIFreeDeviceInfo)(infos);
+
+            let mut ids: Vec<c_int> = self.pen_devices.iter().map(|pen| pen.id).collect();
+            ids.extend(self.scroll_devices.iter().map(|scroll| scroll.id));
+            ids.sort_unstable();
+            ids.dedup();
+
+            let mut masks = vec![[0u8; 4]; ids.len()];
+            let mut event_masks: Vec<_> = ids
+                .iter()
+                .zip(masks.iter_mut())
+                .map(|(&id, mask)| {
+                    xinput2::XISetMask(mask, xinput2::XI_Motion);
+                    xinput2::XISetMask(mask, xinput2::XI_ButtonPress);
+                    xinput2::XISetMask(mask, xinput2::XI_ButtonRelease);
+                    xinput2::XIEventMask {
+                        deviceid: id,
+                        mask_len: mask.len() as c_int,
+                        mask: mask.as_mut_ptr(),
+                    }
+                })
+                .collect();
+
+            if !event_masks.is_empty() {
+                (xinput2.XISelectEvents)(
+                    self.d.display,
+                    self.handle,
+                    event_masks.as_mut_ptr(),
+                    event_masks.len() as c_int,
+                );
+            }
+        }
+
+        self.read_scroll_valuators();
+    }
+
+    /// Reads the current values of the scroll valuators, so the first scroll event has a
+    /// value to compare with
+    fn read_scroll_valuators(&mut self) {
+        let xinput2 = match &self.d.xinput2 {
+            Some(xinput2) => xinput2,
+            None => return,
+        };
+
+        for device in &mut self.scroll_devices {
+            unsafe {
+                let mut count = 0;
+                let infos = (xinput2.XIQueryDevice)(self.d.display, device.id, &mut count);
+                if infos.is_null() {
+                    continue;
+                }
+
+                for info in std::slice::from_raw_parts(infos, count as usize) {
+                    let classes =
+                        std::slice::from_raw_parts(info.classes, info.num_classes as usize);
+                    for &class in classes {
+                        if (*class)._type != xinput2::XIValuatorClass {
+                            continue;
+                        }
+
+                        let valuator = &*(class as *const xinput2::XIValuatorClassInfo);
+                        for axis in device.axes.iter_mut().flatten() {
+                            if axis.number == valuator.number {
+                                axis.last = Some(valuator.value);
+                            }
+                        }
+                    }
+                }
+
+                (xinput2.XIFreeDeviceInfo)(infos);
+            }
+        }
+    }
+
+    /// Remembers the wheel buttons emulated from the scroll valuators, the XI2 event of the slave
+    /// device is received before the core event of the master pointer
+    unsafe fn process_emulated_wheel(&mut self, cookie: &xlib::XGenericEventCookie) {
+        let event = &*(cookie.data as *const xinput2::XIDeviceEvent);
+        if event.event != self.handle || event.flags & xinput2::XIPointerEmulated == 0 {
+            return;
+        }
+
+        // the button may be mapped differently for the core events, so only the time is kept
+        let wheel = (xlib::Button4..=Button7).contains(&(event.detail as c_uint));
+        let scrolled = self
+            .scroll_devices
+            .iter()
+            .any(|scroll| scroll.id == event.deviceid);
+        if wheel && scrolled {
+            self.emulated_wheel.push(event.time);
+        }
+    }
+
+    /// Reports the scrolling of a device with smooth scrolling. The scroll valuators are
+    /// absolute so the distance is the difference with the previous value.
+    unsafe fn process_scroll_event(&mut self, cookie: &xlib::XGenericEventCookie) {
+        let event = &*(cookie.data as *const xinput2::XIDeviceEvent);
+        let device = match self
+            .scroll_devices
+            .iter_mut()
+            .find(|scroll| scroll.id == event.deviceid)
+        {
+            Some(device) if event.event == self.handle => device,
+            _ => return,
+        };
+
+        let mut notches = [0.0; 2];
+        let mask =
+            std::slice::from_raw_parts(event.valuators.mask, event.valuators.mask_len as usize);
+        let mut values = event.valuators.values;
+        for number in 0..(mask.len() * 8) as c_int {
+            if mask[number as usize / 8] & (1 << (number % 8)) == 0 {
+                continue;
+            }
+
+            let value = *values;
+            values = values.add(1);
+
+            for (axis, notch) in device.axes.iter_mut().zip(notches.iter_mut()) {
+                if let Some(axis) = axis.as_mut().filter(|axis| axis.number == number) {
+                    if let Some(last) = axis.last {
+                        *notch = ((value - last) / axis.increment) as f32;
+                    }
+                    axis.last = Some(value);
+                }
+            }
+        }
+
+        let source = device.source;
+        if notches == [0.0; 2] {
+            return;
+        }
+
+        // the valuators increases when scrolling down and to the right
+        let notches = (notches[0], -notches[1]);
+        self.scroll_x -= notches.0;
+        self.scroll_y += notches.1;
+
+        self.events.set_time_ms(event.time as u32);
+        self.events.push(Event::Scroll {
+            source,
+            pixels: (notches.0 * PIXELS_PER_NOTCH, notches.1 * PIXELS_PER_NOTCH),
+            notches,
+        });
+    }
+
+    unsafe fn process_pen_event(&mut self, cookie: &xlib::XGenericEventCookie) {
+        let event = &*(cookie.data as *const xinput2::XIDeviceEvent);
+        let device = match self.pen_devices.iter().find(|pen| pen.id == event.deviceid) {
+            Some(&device) if event.event == self.handle => device,
+            _ => return,
+        };
+
+        let mut pen = self.pen.unwrap_or_default();
+        let scale = self.scale as f32;
+        pen.x = event.event_x as f32 / scale;
+        pen.y = event.event_y as f32 / scale;
+        pen.eraser = device.eraser;
+
+        // the values are only present for the axes set in the mask
+        let mask =
+            std::slice::from_raw_parts(event.valuators.mask, event.valuators.mask_len as usize);
+        let mut values = event.valuators.values;
+        for number in 0..(mask.len() * 8) as c_int {
+            if mask[number as usize / 8] & (1 << (number % 8)) == 0 {
+                continue;
+            }
+
+            let value = *values;
+            values = values.add(1);
+
+            match device.pressure {
+                Some((pressure, min, max)) if pressure == number && max > min => {
+                    pen.pressure = ((value - min) / (max - min)) as f32;
+                }
+                _ if device.tilt_x == Some(number) => pen.tilt_x = value as f32,
+                _ if device.tilt_y == Some(number) => pen.tilt_y = value as f32,
+                _ => (),
+            }
         }
 
-        // in X, the mouse wheel is usually mapped to Button4/5
+        // the tip is the first button followed by the barrel buttons
+        if cookie.evtype != xinput2::XI_Motion {
+            let pressed = cookie.evtype == xinput2::XI_ButtonPress;
+            match event.detail {
+                1 => pen.in_contact = pressed,
+                2 => pen.barrel_buttons[0] = pressed,
+                3 => pen.barrel_buttons[1] = pressed,
+                _ => (),
+            }
+        }
+
+        if self.pen != Some(pen) {
+            self.pen = Some(pen);
+            self.events.set_time_ms(event.time as u32);
+            self.events.push(Event::Pen(pen));
+        }
+    }
+
+    unsafe fn process_touch(&mut self, cookie: &xlib::XGenericEventCookie, phase: TouchPhase) {
+        let event = &*(cookie.data as *const xinput2::XIDeviceEvent);
+        if event.event != self.handle {
+            return;
+        }
+
+        let scale = self.scale as f32;
+        let id = event.detail as u32 as u64;
+        let (x, y) = (event.event_x as f32 / scale, event.event_y as f32 / scale);
+
+        match phase {
+            TouchPhase::Begin => self.touches.push(Touch { id, x, y }),
+            TouchPhase::Move => {
+                if let Some(touch) = self.touches.iter_mut().find(|touch| touch.id == id) {
+                    touch.x = x;
+                    touch.y = y;
+                }
+            }
+            _ => self.touches.retain(|touch| touch.id != id),
+        }
+
+        self.events.set_time_ms(event.time as u32);
+        self.events.push(Event::Touch { id, phase, x, y });
+
+        // selecting touch events stops the server from emulating the mouse for this window,
+        // so do it here for the touch that would have been emulated
+        if event.flags & xinput2::XITouchEmulatingPointer != 0 {
+            self.update_mouse_pos(event.event_x as c_int, event.event_y as c_int);
+
+            let pressed = match phase {
+                TouchPhase::Begin => true,
+                TouchPhase::End => false,
+                _ => return,
+            };
+            self.buttons[0] = pressed as u8;
+            let clicks = if pressed {
+                let (x, y) = (event.event_x as f32, event.event_y as f32);
+                self.clicks
+                    .press(MouseButton::Left, event.time as u32, x, y)
+            } else {
+                self.clicks.release(MouseButton::Left)
+            };
+            self.events.push(Event::MouseButton {
+                button: MouseButton::Left,
+                pressed,
+                clicks,
+            });
+        }
+    }
+
+    unsafe fn process_xdnd_message(&mut self, message: &xlib::XClientMessageEvent) {
+        let xdnd = self.d.xdnd;
+        let message_type = message.message_type;
+        let source = message.data.get_long(0) as xlib::Window;
+
+        if message_type == xdnd.enter {
+            let flags = message.data.get_long(1);
+
+            // sources with more than 3 types puts them in a property
+            let types = if flags & 1 != 0 {
+                self.read_property(source, xdnd.type_list, false)
+                    .map(|(_, data)| atoms_from_bytes(&data))
+                    .unwrap_or_default()
+            } else {
+                (2..5)
+                    .map(|i| message.data.get_long(i) as xlib::Atom)
+                    .collect()
+            };
+
+            self.drag = Some(XdndDrag {
+                source,
+                version: flags >> 24,
+                accepted: types.contains(&xdnd.uri_list),
+                entered: false,
+            });
+            return;
+        }
+
+        let drag = match self.drag {
+            Some(drag) if drag.source == source => drag,
+            _ => return,
+        };
+
+        if message_type == xdnd.position {
+            let position = message.data.get_long(2);
+            let (root_x, root_y) = ((position >> 16) & 0xffff, position & 0xffff);
+
+            let mut x = 0;
+            let mut y = 0;
+            let mut child = 0;
+            (self.d.lib.XTranslateCoordinates)(
+                self.d.display,
+                (self.d.lib.XDefaultRootWindow)(self.d.display),
+                self.handle,
+                root_x as c_int,
+                root_y as c_int,
+                &mut x,
+                &mut y,
+                &mut child,
+            );
+
+            if drag.accepted {
+                let scale = self.scale as f32;
+                let (x, y) = (x as f32 / scale, y as f32 / scale);
+
+                if drag.version >= 1 {
+                    self.events.set_time_ms(message.data.get_long(3) as u32);
+                }
+
+                if drag.entered {
+                    self.events.push(Event::DragMove { x, y });
+                } else {
+                    self.events.push(Event::DragEnter { x, y });
+                    self.drag = Some(XdndDrag {
+                        entered: true,
+                        ..drag
+                    });
+                }
+            }
+
+            let action = if drag.accepted { xdnd.action_copy } else { 0 };
+            self.send_xdnd_message(
+                source,
+                xdnd.status,
+                [
+                    self.handle as c_long,
+                    drag.accepted as c_long,
+                    0,
+                    0,
+                    action as c_long,
+                ],
+            );
+        } else if message_type == xdnd.leave {
+            if drag.entered {
+                self.events.push(Event::DragLeave);
+            }
+            self.drag = None;
+        } else if message_type == xdnd.drop {
+            let time = if drag.version >= 1 {
+                message.data.get_long(2) as xlib::Time
+            } else {
+                xlib::CurrentTime
+            };
+
+            let paths = if drag.accepted {
+                self.convert_selection(xdnd.selection, xdnd.uri_list, time)
+                    .map(|uri_list| parse_uri_list(&uri_list))
+                    .unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+
+            let success = !paths.is_empty();
+            let action = if success { xdnd.action_copy } else { 0 };
+            self.send_xdnd_message(
+                source,
+                xdnd.finished,
+                [
+                    self.handle as c_long,
+                    success as c_long,
+                    action as c_long,
+                    0,
+                    0,
+                ],
+            );
+
+            if success {
+                self.events.push(Event::Drop(paths));
+            } else if drag.entered {
+                self.events.push(Event::DragLeave);
+            }
+            self.drag = None;
+        }
+    }
+
+    unsafe fn send_xdnd_message(
+        &mut self,
+        target: xlib::Window,
+        message_type: xlib::Atom,
+        data: [c_long; 5],
+    ) {
+        let mut message = xlib::XClientMessageEvent {
+            type_: xlib::ClientMessage,
+            serial: 0,
+            send_event: xlib::True,
+            display: self.d.display,
+            window: target,
+            message_type,
+            format: 32,
+            data: xlib::ClientMessageData::new(),
+        };
+        message.data.as_longs_mut()[..5].copy_from_slice(&data);
+
+        let mut event = xlib::XEvent {
+            client_message: message,
+        };
+        (self.d.lib.XSendEvent)(self.d.display, target, xlib::False, 0, &mut event);
+        (self.d.lib.XFlush)(self.d.display);
+    }
+
+    /// Waits (for a limited time) for an event of the given type sent to our window
+    unsafe fn wait_for_event(&mut self, event_type: c_int, event: &mut xlib::XEvent) -> bool {
+        let deadline = std::time::Instant::now() + SELECTION_TIMEOUT;
+        let fd = (self.d.lib.XConnectionNumber)(self.d.display);
+
+        loop {
+            // reads what the server has sent so far
+            if (self.d.lib.XCheckTypedWindowEvent)(self.d.display, self.handle, event_type, event)
+                != 0
+            {
+                return true;
+            }
+
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            if remaining == Duration::from_secs(0) {
+                return false;
+            }
+
+            // sleeps until the server sends something (rounding up so it doesn't spin)
+            let mut poll_fd = libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            let timeout = (remaining.as_micros() as c_int + 999) / 1000;
+            libc::poll(&mut poll_fd, 1, timeout);
+        }
+    }
+
+    /// Uses the auto repeat delay and interval of the X server as the defaults
+    unsafe fn read_key_repeat(&mut self) {
+        const XKB_USE_CORE_KBD: c_uint = 0x100;
+
+        // keys such as the modifiers aren't repeated
+        let mut state: xlib::XKeyboardState = std::mem::zeroed();
+        (self.d.lib.XGetKeyboardControl)(self.d.display, &mut state);
+        if state.global_auto_repeat == xlib::AutoRepeatModeOff {
+            self.auto_repeat_keys = [0; 32];
+        } else {
+            for (keys, &repeats) in self.auto_repeat_keys.iter_mut().zip(&state.auto_repeats) {
+                *keys = repeats as u8;
+            }
+        }
+
+        let mut delay = 0;
+        let mut interval = 0;
+
+        if (self.d.lib.XkbGetAutoRepeatRate)(
+            self.d.display,
+            XKB_USE_CORE_KBD,
+            &mut delay,
+            &mut interval,
+        ) != 0
+            && interval > 0
+        {
+            self.key_handler
+                .set_system_key_repeat(delay as f32 / 1000.0, interval as f32 / 1000.0);
+        }
+    }
+
+    fn is_auto_repeat_key(&self, scancode: Scancode) -> bool {
+        let keycode = scancode.0 as usize + 8;
+        self.auto_repeat_keys
+            .get(keycode / 8)
+            .is_some_and(|keys| keys & (1 << (keycode % 8)) != 0)
+    }
+
+    /// Reads the double click settings of the desktop from XSettings (which GTK and Qt uses)
+    unsafe fn read_xsettings(&mut self) {
+        let screen = (self.d.lib.XDefaultScreen)(self.d.display);
+        let selection_name = CString::new(format!("_XSETTINGS_S{}", screen)).unwrap();
+        let selection = (self.d.lib.XInternAtom)(self.d.display, selection_name.as_ptr(), 0);
+        let settings = self.d.intern_atom(b"_XSETTINGS_SETTINGS\0", false);
+
+        let owner = (self.d.lib.XGetSelectionOwner)(self.d.display, selection);
+        if owner == 0 {
+            return;
+        }
+
+        if let Some((_, data)) = self.read_property(owner, settings, false) {
+            if let Some(time) = xsettings_int(&data, "Net/DoubleClickTime") {
+                self.clicks.desktop_interval = u32::try_from(time).ok();
+            }
+            if let Some(distance) = xsettings_int(&data, "Net/DoubleClickDistance") {
+                self.clicks.distance = distance as f32;
+            }
+        }
+    }
+
+    /// Reads (and optionally deletes) a property on a window, returning the type and the data
+    unsafe fn read_property(
+        &self,
+        window: xlib::Window,
+        property: xlib::Atom,
+        delete: bool,
+    ) -> Option<(xlib::Atom, Vec<u8>)> {
+        let mut actual_type: xlib::Atom = 0;
+        let mut format: c_int = 0;
+        let mut item_count: c_ulong = 0;
+        let mut bytes_after: c_ulong = 0;
+        let mut data: *mut c_uchar = std::ptr::null_mut();
+
+        if (self.d.lib.XGetWindowProperty)(
+            self.d.display,
+            window,
+            property,
+            0,
+            c_long::MAX / 4,
+            delete as c_int,
+            xlib::AnyPropertyType as xlib::Atom,
+            &mut actual_type,
+            &mut format,
+            &mut item_count,
+            &mut bytes_after,
+            &mut data,
+        ) != xlib::Success as c_int
+        {
+            return None;
+        }
+
+        // Xlib returns 16 and 32 bit items as shorts and longs
+        let item_size = match format {
+            8 => 1,
+            16 => std::mem::size_of::<c_short>(),
+            32 => std::mem::size_of::<c_long>(),
+            _ => 0,
+        };
+
+        let mut bytes = Vec::new();
+        if !data.is_null() {
+            bytes.extend_from_slice(std::slice::from_raw_parts(
+                data,
+                item_count as usize * item_size,
+            ));
+            (self.d.lib.XFree)(data as *mut c_void);
+        }
+
+        Some((actual_type, bytes))
+    }
+
+    /// Asks the owner of `selection` to convert it to `target` and waits for the data
+    unsafe fn convert_selection(
+        &mut self,
+        selection: xlib::Atom,
+        target: xlib::Atom,
+        time: xlib::Time,
+    ) -> Option<Vec<u8>> {
+        let property = self.d.selection_property;
+
+        (self.d.lib.XDeleteProperty)(self.d.display, self.handle, property);
+        (self.d.lib.XConvertSelection)(
+            self.d.display,
+            selection,
+            target,
+            property,
+            self.handle,
+            time,
+        );
+
+        let mut event: xlib::XEvent = std::mem::zeroed();
+        if !self.wait_for_event(xlib::SelectionNotify, &mut event) || event.selection.property == 0
+        {
+            return None;
+        }
+
+        let (actual_type, data) = self.read_property(self.handle, property, true)?;
+        if actual_type != self.d.incr {
+            return Some(data);
+        }
+
+        // Large data is sent in chunks, each one as a new value of the property. Deleting the
+        // property (done by read_property) tells the owner to send the next one and an empty
+        // chunk ends the transfer.
+        let mut data = Vec::new();
+        loop {
+            if !self.wait_for_event(xlib::PropertyNotify, &mut event) {
+                return None;
+            }
+
+            // the other property changes of our window are taken from the queue too
+            if event.property.atom == self.d.net_wm.state {
+                self.read_wm_state();
+            }
+            if event.property.atom != property || event.property.state != xlib::PropertyNewValue {
+                continue;
+            }
+
+            let (_, chunk) = self.read_property(self.handle, property, true)?;
+            if chunk.is_empty() {
+                return Some(data);
+            }
+
+            data.extend_from_slice(&chunk);
+        }
+    }
+
+    fn selection_atom(&self, selection: Selection) -> xlib::Atom {
+        match selection {
+            Selection::Clipboard => self.d.clipboard,
+            Selection::Primary => xlib::XA_PRIMARY,
+        }
+    }
+
+    /// Returns the data we serve for a selection or `None` for selections we don't handle
+    fn owned_selection(&self, selection: xlib::Atom) -> Option<&[(xlib::Atom, Vec<u8>)]> {
+        if selection == self.d.clipboard {
+            Some(&self.clipboard)
+        } else if selection == xlib::XA_PRIMARY {
+            Some(&self.primary)
+        } else {
+            None
+        }
+    }
+
+    fn owned_selection_mut(
+        &mut self,
+        selection: xlib::Atom,
+    ) -> Option<&mut Vec<(xlib::Atom, Vec<u8>)>> {
+        if selection == self.d.clipboard {
+            Some(&mut self.clipboard)
+        } else if selection == xlib::XA_PRIMARY {
+            Some(&mut self.primary)
+        } else {
+            None
+        }
+    }
+
+    pub fn set_selection(&mut self, selection: Selection, data: ClipboardData) {
+        let selection = self.selection_atom(selection);
+        let data = data
+            .into_iter()
+            .map(|(mime_type, bytes)| (self.d.mime_type_atom(mime_type), bytes))
+            .collect();
+
+        if let Some(owned) = self.owned_selection_mut(selection) {
+            *owned = data;
+        }
+
+        unsafe {
+            let time = self.last_server_time;
+            (self.d.lib.XSetSelectionOwner)(self.d.display, selection, self.handle, time);
+
+            // the request is ignored if someone else took the selection after our event
+            if (self.d.lib.XGetSelectionOwner)(self.d.display, selection) != self.handle {
+                if let Some(owned) = self.owned_selection_mut(selection) {
+                    owned.clear();
+                }
+            }
+        }
+    }
+
+    // is_none_or would need Rust 1.82
+    #[allow(clippy::unnecessary_map_or)]
+    pub fn get_selection(&mut self, selection: Selection, mime_types: &[&str]) -> Option<Vec<u8>> {
+        let selection = self.selection_atom(selection);
+        let targets: Vec<xlib::Atom> = mime_types
+            .iter()
+            .map(|mime_type| self.d.mime_type_atom(mime_type))
+            .collect();
+
+        unsafe {
+            // we would wait for ourselves to answer so use the data directly
+            if (self.d.lib.XGetSelectionOwner)(self.d.display, selection) == self.handle {
+                let owned = self.owned_selection(selection)?;
+                return targets.iter().find_map(|target| {
+                    owned
+                        .iter()
+                        .find(|(owned, _)| owned == target)
+                        .map(|(_, bytes)| bytes.clone())
+                });
+            }
+
+            // ask for the supported targets first to avoid waiting for conversions that fails
+            let time = self.last_server_time;
+            let supported = self
+                .convert_selection(selection, self.d.targets, time)
+                .map(|data| atoms_from_bytes(&data));
+
+            for target in targets {
+                if supported.as_ref().map_or(true, |s| s.contains(&target)) {
+                    if let Some(data) = self.convert_selection(selection, target, time) {
+                        return Some(data);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    unsafe fn process_button(&mut self, ev: xlib::XEvent, is_down: bool) {
+        let button = match ev.button.button {
+            xlib::Button1 => Some((0, MouseButton::Left)),
+            xlib::Button2 => Some((1, MouseButton::Middle)),
+            xlib::Button3 => Some((2, MouseButton::Right)),
+            Button8 => Some((3, MouseButton::Back)),
+            Button9 => Some((4, MouseButton::Forward)),
+            _ => None,
+        };
+
+        if let Some((index, button)) = button {
+            self.buttons[index] = if is_down { 1 } else { 0 };
+            let clicks = if is_down {
+                let (x, y) = (ev.button.x as f32, ev.button.y as f32);
+                self.clicks.press(button, ev.button.time as u32, x, y)
+            } else {
+                self.clicks.release(button)
+            };
+            self.events.push(Event::MouseButton {
+                button,
+                pressed: is_down,
+                clicks,
+            });
+            return;
+        }
+
+        // in X, the mouse wheel is usually mapped to Button4/5. These are emulated from the smooth
+        // scrolling which is used if the device has it
+        if (xlib::Button4..=Button7).contains(&ev.button.button) {
+            let time = ev.button.time;
+            // the emulated buttons that didn't get a core event (such as while grabbed) are dropped
+            self.emulated_wheel.retain(|&emulated| emulated >= time);
+            if let Some(index) = self.emulated_wheel.iter().position(|&t| t == time) {
+                self.emulated_wheel.remove(index);
+                return;
+            }
+        }
 
         let scroll: (i32, i32) = match ev.button.button {
             xlib::Button4 => (0, 10),
@@ -1218,16 +3734,42 @@ impl Window {
             }
         };
 
-        self.scroll_x += scroll.0 as f32 * 0.1;
-        self.scroll_y += scroll.1 as f32 * 0.1;
+        let (x, y) = (scroll.0 as f32 * 0.1, scroll.1 as f32 * 0.1);
+        self.scroll_x += x;
+        self.scroll_y += y;
+
+        // X sends a release for the wheel buttons as well, only report the press as an event
+        if is_down {
+            self.events.push(Event::Scroll {
+                source: ScrollSource::Wheel,
+                pixels: (-x * PIXELS_PER_NOTCH, y * PIXELS_PER_NOTCH),
+                notches: (-x, y),
+            });
+        }
     }
 
-    fn update_key_state(&mut self, sym: xlib::KeySym, is_down: bool) {
-        if sym > u32::MAX as xlib::KeySym {
+    /// The server repeats are passed on as they are unless the application has set its own
+    /// repeat delay or rate, in that case they are generated here instead
+    fn process_key_repeat(&mut self) {
+        if !self.key_handler.is_key_repeat_overridden() {
             return;
         }
 
-        let key = match sym as u32 {
+        while let Some((key, scancode, time)) = self.key_handler.next_repeat() {
+            self.events.set_time_ms(time);
+            self.events.push(Event::KeyDown {
+                key,
+                scancode,
+                modifiers: self.key_handler.get_modifiers(),
+                repeat: true,
+            });
+        }
+    }
+
+    fn update_key_state(&mut self, sym: xlib::KeySym, scancode: Scancode, is_down: bool) {
+        let sym = u32::try_from(sym).unwrap_or(xlib::NoSymbol as u32);
+
+        let key = match sym {
             XK_0 => Key::Key0,
             XK_1 => Key::Key1,
             XK_2 => Key::Key2,
@@ -1337,28 +3879,257 @@ impl Window {
             XK_KP_Add => Key::NumPadPlus,
             XK_KP_Enter => Key::NumPadEnter,
 
-            _ => {
-                // ignore other keys
-                return;
-            }
+            _ => Key::Unknown,
         };
 
-        self.key_handler.set_key_state(key, is_down);
+        // with detectable auto repeat the server only sends the presses for repeats
+        let repeat = is_down && self.key_handler.is_scancode_down(scancode);
+
+        if key != Key::Unknown {
+            self.key_handler.set_key_state(key, is_down);
+        }
+
+        self.key_handler.set_scancode_state(scancode, is_down);
+
+        if !is_down {
+            self.key_handler.stop_repeat(scancode);
+        } else if !repeat {
+            if self.is_auto_repeat_key(scancode) {
+                let time = self.events.time_ms();
+                self.key_handler.start_repeat(key, scancode, time);
+            }
+        } else if self.key_handler.is_key_repeat_overridden() {
+            // repeated by `process_key_repeat` (the text is still repeated by the server)
+            return;
+        }
+
+        self.events.push(if is_down {
+            Event::KeyDown {
+                key,
+                scancode,
+                modifiers: self.key_handler.get_modifiers(),
+                repeat,
+            }
+        } else {
+            Event::KeyUp {
+                key,
+                scancode,
+                modifiers: self.key_handler.get_modifiers(),
+            }
+        });
     }
 }
 
 impl Drop for Window {
     fn drop(&mut self) {
         unsafe {
+            self.restore_video_mode();
             self.free_image();
 
             // TODO  [ andrewj: right now DisplayInfo is not shared, so doing this is
             //                  probably pointless ]
             // XSaveContext(s_display, info->window, s_context, (XPointer)0);
 
+            self.free_custom_cursor();
             (self.d.lib.XDestroyIC)(self.xic);
             (self.d.lib.XCloseIM)(self.xim);
             (self.d.lib.XDestroyWindow)(self.d.display, self.handle);
         }
     }
 }
+
+/// The refresh rate of a video mode in millihertz
+fn mode_refresh_rate(mode: &xrandr::XRRModeInfo) -> u32 {
+    let mut lines = mode.vTotal as f64;
+    if mode.modeFlags & xrandr::RR_DoubleScan as c_ulong != 0 {
+        lines *= 2.0;
+    }
+    if mode.modeFlags & xrandr::RR_Interlace as c_ulong != 0 {
+        lines /= 2.0;
+    }
+
+    let pixels = mode.hTotal as f64 * lines;
+    if pixels == 0.0 {
+        return 0;
+    }
+
+    (mode.dotClock as f64 * 1000.0 / pixels).round() as u32
+}
+
+/// Views an array returned by XRandR, which may be null if it's empty
+unsafe fn xrandr_slice<'a, T>(data: *const T, len: c_int) -> &'a [T] {
+    if data.is_null() || len <= 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(data, len as usize)
+    }
+}
+
+/// Finds an integer setting in the `_XSETTINGS_SETTINGS` property
+fn xsettings_int(data: &[u8], name: &str) -> Option<i32> {
+    // the first byte is the byte order (1 for big endian)
+    let big_endian = *data.first()? == 1;
+    let read_u16 = |offset: usize| {
+        let bytes = data.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    };
+    let read_u32 = |offset: usize| {
+        let bytes = data.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    };
+    let pad = |len: usize| (len + 3) & !3;
+
+    let count = read_u32(8)?;
+    let mut offset = 12;
+
+    for _ in 0..count {
+        // type, unused byte, name length, name and serial of the last change
+        let setting_type = *data.get(offset)?;
+        let name_len = read_u16(offset + 2)? as usize;
+        let setting_name = data.get(offset + 4..offset + 4 + name_len)?;
+        offset += 4 + pad(name_len) + 4;
+
+        match setting_type {
+            // integer
+            0 if setting_name == name.as_bytes() => return Some(read_u32(offset)? as i32),
+            0 => offset += 4,
+            // string
+            1 => offset += 4 + pad(read_u32(offset)? as usize),
+            // color
+            2 => offset += 8,
+            _ => return None,
+        }
+    }
+
+    None
+}
+
+/// Converts the data of a property of type ATOM (returned as longs by Xlib) to atoms
+fn atoms_from_bytes(data: &[u8]) -> Vec<xlib::Atom> {
+    data.chunks_exact(std::mem::size_of::<c_long>())
+        .map(|atom| unsafe { std::ptr::read_unaligned(atom.as_ptr() as *const xlib::Atom) })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    enum Setting<'a> {
+        Int(&'a str, u32),
+        String(&'a str, &'a str),
+        Color(&'a str, [u16; 4]),
+    }
+
+    // builds a _XSETTINGS_SETTINGS property as described in the XSETTINGS specification
+    fn xsettings(big_endian: bool, settings: &[Setting]) -> Vec<u8> {
+        let u16_bytes = |value: u16| {
+            if big_endian {
+                value.to_be_bytes()
+            } else {
+                value.to_le_bytes()
+            }
+        };
+        let u32_bytes = |value: u32| {
+            if big_endian {
+                value.to_be_bytes()
+            } else {
+                value.to_le_bytes()
+            }
+        };
+        let padded = |data: &mut Vec<u8>, bytes: &[u8]| {
+            data.extend_from_slice(bytes);
+            data.resize((data.len() + 3) & !3, 0);
+        };
+
+        let mut data = vec![big_endian as u8, 0, 0, 0];
+        data.extend_from_slice(&u32_bytes(1));
+        data.extend_from_slice(&u32_bytes(settings.len() as u32));
+
+        for setting in settings {
+            let (setting_type, name) = match *setting {
+                Setting::Int(name, _) => (0, name),
+                Setting::String(name, _) => (1, name),
+                Setting::Color(name, _) => (2, name),
+            };
+            data.extend_from_slice(&[setting_type, 0]);
+            data.extend_from_slice(&u16_bytes(name.len() as u16));
+            padded(&mut data, name.as_bytes());
+            data.extend_from_slice(&u32_bytes(0));
+
+            match *setting {
+                Setting::Int(_, value) => data.extend_from_slice(&u32_bytes(value)),
+                Setting::String(_, value) => {
+                    data.extend_from_slice(&u32_bytes(value.len() as u32));
+                    padded(&mut data, value.as_bytes());
+                }
+                Setting::Color(_, color) => {
+                    for channel in color {
+                        data.extend_from_slice(&u16_bytes(channel));
+                    }
+                }
+            }
+        }
+
+        data
+    }
+
+    #[test]
+    fn xsettings_byte_order() {
+        let settings = [Setting::Int("Net/DoubleClickTime", 400)];
+
+        for big_endian in [false, true] {
+            let data = xsettings(big_endian, &settings);
+            assert_eq!(xsettings_int(&data, "Net/DoubleClickTime"), Some(400));
+            assert_eq!(xsettings_int(&data, "Net/DoubleClickDistance"), None);
+        }
+    }
+
+    #[test]
+    fn xsettings_skipped_types() {
+        let settings = [
+            Setting::String("Net/ThemeName", "Adwaita"),
+            Setting::Color("Gtk/Color", [1, 2, 3, 4]),
+            Setting::Int("Gtk/CursorBlink", 1),
+            Setting::String("Gtk/FontName", "Cantarell 11"),
+            Setting::Int("Net/DoubleClickDistance", 5),
+        ];
+
+        for big_endian in [false, true] {
+            let data = xsettings(big_endian, &settings);
+            assert_eq!(xsettings_int(&data, "Net/DoubleClickDistance"), Some(5));
+            assert_eq!(xsettings_int(&data, "Gtk/CursorBlink"), Some(1));
+            // only integers are returned
+            assert_eq!(xsettings_int(&data, "Net/ThemeName"), None);
+        }
+    }
+
+    #[test]
+    fn xsettings_malformed() {
+        let data = xsettings(false, &[Setting::Int("Net/DoubleClickTime", 400)]);
+
+        assert_eq!(xsettings_int(&[], "Net/DoubleClickTime"), None);
+        assert_eq!(
+            xsettings_int(&data[..data.len() - 2], "Net/DoubleClickTime"),
+            None
+        );
+
+        // unknown setting type
+        let mut unknown = data.clone();
+        unknown[12] = 3;
+        assert_eq!(xsettings_int(&unknown, "Net/DoubleClickTime"), None);
+
+        // more settings than there are in the data
+        let mut count = data;
+        count[8] = 2;
+        assert_eq!(xsettings_int(&count, "Net/Other"), None);
+    }
+}
@@ -44,6 +44,16 @@ pub enum xkb_keymap_format {
 
 pub type xkb_state_component = u32;
 
+/** The effective modifiers (depressed, latched and locked combined) */
+pub const XKB_STATE_MODS_EFFECTIVE: xkb_state_component = 1 << 3;
+
+pub const XKB_MOD_NAME_SHIFT: &[u8] = b"Shift\0";
+pub const XKB_MOD_NAME_CAPS: &[u8] = b"Lock\0";
+pub const XKB_MOD_NAME_CTRL: &[u8] = b"Control\0";
+pub const XKB_MOD_NAME_ALT: &[u8] = b"Mod1\0";
+pub const XKB_MOD_NAME_NUM: &[u8] = b"Mod2\0";
+pub const XKB_MOD_NAME_LOGO: &[u8] = b"Mod4\0";
+
 external_library!(XkbCommon, "xkbcommon",
 functions:
     fn xkb_keysym_to_utf32(xkb_keysym_t) -> u32,
@@ -55,6 +65,7 @@ functions:
                                   xkb_keymap_compile_flags
                                  ) -> *mut xkb_keymap,
     fn xkb_keymap_unref(*mut xkb_keymap) -> (),
+    fn xkb_keymap_key_repeats(*mut xkb_keymap, xkb_keycode_t) -> i32,
     fn xkb_state_key_get_one_sym(*mut xkb_state, xkb_keycode_t) -> xkb_keysym_t,
     fn xkb_state_new(*mut xkb_keymap) -> *mut xkb_state,
     fn xkb_state_unref(*mut xkb_state) -> (),
@@ -66,6 +77,7 @@ functions:
                              xkb_layout_index_t,
                              xkb_layout_index_t
                             ) -> xkb_state_component,
+    fn xkb_state_mod_name_is_active(*mut xkb_state, *const c_char, xkb_state_component) -> i32,
 );
 
 #[cfg(feature = "dlopen")]
@@ -242,8 +242,9 @@ impl Window {
     }
 
     #[inline]
-    pub fn topmost(&self, _topmost: bool) {
+    pub fn topmost(&self, _topmost: bool) -> Result<()> {
         // TODO?
+        Ok(())
     }
 
     #[inline]
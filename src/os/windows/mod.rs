@@ -518,7 +518,7 @@ pub struct Window {
     accel_table: windef::HACCEL,
     accel_key: usize,
     cursor: CursorStyle,
-    cursors: [windef::HCURSOR; 8],
+    cursors: [windef::HCURSOR; 26],
     draw_params: DrawParameters,
     mouse: MouseData,
 }
@@ -687,6 +687,25 @@ impl Window {
                     winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_SIZEWE),
                     winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_SIZENS),
                     winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_SIZEALL),
+                    winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_WAIT),
+                    winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_APPSTARTING),
+                    winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_NO),
+                    winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_HAND),
+                    winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_SIZENESW),
+                    winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_SIZENWSE),
+                    winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_SIZENS),
+                    winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_SIZENS),
+                    winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_SIZEWE),
+                    winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_SIZEWE),
+                    winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_SIZENWSE),
+                    winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_SIZENESW),
+                    winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_SIZENESW),
+                    winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_SIZENWSE),
+                    // Windows has no zoom cursors
+                    winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_ARROW),
+                    winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_ARROW),
+                    winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_HELP),
+                    winuser::LoadCursorW(std::ptr::null_mut(), winuser::IDC_SIZEALL),
                 ],
                 draw_params: DrawParameters {
                     scale_mode: opts.scale_mode,
@@ -695,7 +714,7 @@ impl Window {
             };
 
             if opts.topmost {
-                window.topmost(true)
+                window.topmost(true)?;
             }
 
             Ok(window)
@@ -799,7 +818,7 @@ impl Window {
     }
 
     #[inline]
-    pub fn topmost(&self, topmost: bool) {
+    pub fn topmost(&self, topmost: bool) -> Result<()> {
         unsafe {
             winuser::SetWindowPos(
                 self.hwnd,
@@ -815,6 +834,8 @@ impl Window {
                 winuser::SWP_SHOWWINDOW | winuser::SWP_NOSIZE | winuser::SWP_NOMOVE,
             )
         };
+
+        Ok(())
     }
 
     #[inline]